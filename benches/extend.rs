@@ -0,0 +1,73 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use monotonicqueue::MonotonicQueue;
+
+const LEN: usize = 100_000;
+
+fn random_walk() -> Vec<i64> {
+    let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+    let mut price = 0i64;
+    (0..LEN)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            price += (seed % 21) as i64 - 10;
+            price
+        })
+        .collect()
+}
+
+fn extend(c: &mut Criterion) {
+    let ints = random_walk();
+    let floats: Vec<f64> = ints.iter().map(|&n| n as f64 / 100.0).collect();
+
+    let mut group = c.benchmark_group("extend");
+    group.throughput(Throughput::Elements(LEN as u64));
+
+    group.bench_with_input(BenchmarkId::new("push", "i64"), &ints, |b, ints| {
+        b.iter(|| {
+            let mut mq = MonotonicQueue::max();
+            for &n in ints {
+                mq.push(n);
+            }
+            black_box(mq.len())
+        })
+    });
+    group.bench_with_input(
+        BenchmarkId::new("extend_from_slice", "i64"),
+        &ints,
+        |b, ints| {
+            b.iter(|| {
+                let mut mq = MonotonicQueue::max();
+                mq.extend_from_slice(ints);
+                black_box(mq.len())
+            })
+        },
+    );
+
+    group.bench_with_input(BenchmarkId::new("push_by", "f64"), &floats, |b, floats| {
+        b.iter(|| {
+            let mut mq = MonotonicQueue::new();
+            for &n in floats {
+                mq.push_by(n, |n1: &f64, n2: &f64| n1 < n2);
+            }
+            black_box(mq.len())
+        })
+    });
+    group.bench_with_input(
+        BenchmarkId::new("extend_from_slice", "f64"),
+        &floats,
+        |b, floats| {
+            b.iter(|| {
+                let mut mq = MonotonicQueue::with_comparator(|n1: &f64, n2: &f64| n1 < n2);
+                mq.extend_from_slice(floats);
+                black_box(mq.len())
+            })
+        },
+    );
+
+    group.finish();
+}
+
+criterion_group!(benches, extend);
+criterion_main!(benches);
@@ -0,0 +1,433 @@
+use core::fmt;
+use core::mem::MaybeUninit;
+use core::ops::{Deref, DerefMut};
+
+use crate::storage::push_back;
+use crate::{Compare, DequeStorage, Direction, IntoIter, Iter, MonotonicQueue, Policy};
+
+/// Fixed-capacity ring buffer stored inline, holding at most `N` elements.
+///
+/// It backs [`ArrayMonotonicQueue`] and can be plugged into any queue
+/// accepting a [`DequeStorage`]. It never allocates.
+///
+/// # Example
+/// ```
+/// use monotonicqueue::ArrayDeque;
+///
+/// let mut dq: ArrayDeque<i32, 2> = ArrayDeque::new();
+/// assert_eq!(dq.push_back(1), Ok(()));
+/// assert_eq!(dq.push_back(2), Ok(()));
+/// assert_eq!(dq.push_back(3), Err(3));
+/// assert_eq!(dq.pop_front(), Some(1));
+/// ```
+pub struct ArrayDeque<T, const N: usize> {
+    buf: [MaybeUninit<T>; N],
+    head: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> ArrayDeque<T, N> {
+    /// Create an empty array deque.
+    pub const fn new() -> ArrayDeque<T, N> {
+        ArrayDeque {
+            buf: [const { MaybeUninit::uninit() }; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of stored elements.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if no element is stored.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the maximum number of stored elements, `N`.
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Maps a logical index, `0` being the front, to a slot of `buf`. Only
+    /// called with `N > 0`.
+    fn slot(&self, index: usize) -> usize {
+        (self.head + index) % N
+    }
+
+    /// Returns the element at index `index` from the front, or None.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len {
+            // SAFETY: the `len` slots starting at `head` are initialized.
+            Some(unsafe { self.buf[self.slot(index)].assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    /// Provides a peek to the front element, or None.
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Provides a peek to the back element, or None.
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.wrapping_sub(1))
+    }
+
+    /// Appends `item` at the back, or gives it back if the buffer is full.
+    pub fn push_back(&mut self, item: T) -> Result<(), T> {
+        if self.len == N {
+            return Err(item);
+        }
+        let slot = self.slot(self.len);
+        self.buf[slot].write(item);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Prepends `item` at the front, or gives it back if the buffer is full.
+    pub fn push_front(&mut self, item: T) -> Result<(), T> {
+        if self.len == N {
+            return Err(item);
+        }
+        self.head = self.slot(N - 1);
+        self.buf[self.head].write(item);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes the back element and returns it, or None if empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        // SAFETY: the slot was initialized and is no longer counted in `len`.
+        Some(unsafe { self.buf[self.slot(self.len)].assume_init_read() })
+    }
+
+    /// Removes the front element and returns it, or None if empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let slot = self.head;
+        self.head = self.slot(1);
+        self.len -= 1;
+        // SAFETY: the slot was initialized and is no longer counted in `len`.
+        Some(unsafe { self.buf[slot].assume_init_read() })
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayDeque<T, N> {
+    fn drop(&mut self) {
+        while self.pop_back().is_some() {}
+    }
+}
+
+impl<T, const N: usize> Default for ArrayDeque<T, N> {
+    fn default() -> ArrayDeque<T, N> {
+        ArrayDeque::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ArrayDeque<T, N> {
+    fn clone(&self) -> ArrayDeque<T, N> {
+        let mut dq = ArrayDeque::new();
+        for index in 0..self.len {
+            if let Some(item) = self.get(index) {
+                dq.push_back(item.clone()).ok();
+            }
+        }
+        dq
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ArrayDeque<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries((0..self.len).filter_map(|index| self.get(index)))
+            .finish()
+    }
+}
+
+impl<T, const N: usize> DequeStorage<T> for ArrayDeque<T, N> {
+    fn len(&self) -> usize {
+        ArrayDeque::len(self)
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        ArrayDeque::get(self, index)
+    }
+
+    fn push_back(&mut self, item: T) -> Result<(), T> {
+        ArrayDeque::push_back(self, item)
+    }
+
+    fn push_front(&mut self, item: T) -> Result<(), T> {
+        ArrayDeque::push_front(self, item)
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        ArrayDeque::pop_back(self)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        ArrayDeque::pop_front(self)
+    }
+}
+
+/// Array Monotonic Queue is a [`MonotonicQueue`] that retains at most `N`
+/// elements in an [`ArrayDeque`].
+///
+/// It never allocates and is available without the `std` feature, which makes
+/// it suitable for windowed filters on embedded targets. It dereferences to
+/// the `MonotonicQueue` it wraps, whose methods panic once the storage is
+/// full; only `push` and `push_by` are its own, handing the element back
+/// instead.
+///
+/// # Example
+/// ```
+/// use monotonicqueue::ArrayMonotonicQueue;
+///
+/// let mut mq: ArrayMonotonicQueue<i32, 2> = ArrayMonotonicQueue::min();
+/// mq.extend_from_slice(&[4, 1, 3]);
+///
+/// assert_eq!(mq.push(5), Err(5));
+/// assert!(mq.iter().eq(&[1, 3]));
+/// ```
+#[derive(Clone)]
+pub struct ArrayMonotonicQueue<T, const N: usize, C = Direction> {
+    mq: MonotonicQueue<T, C, ArrayDeque<T, N>>,
+}
+
+impl<T, const N: usize> ArrayMonotonicQueue<T, N> {
+    /// Create an empty array monotonic queue whose front holds the maximum.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::ArrayMonotonicQueue;
+    ///
+    /// let mut mq: ArrayMonotonicQueue<i32, 4> = ArrayMonotonicQueue::max();
+    /// mq.push(1).unwrap();
+    /// mq.push(3).unwrap();
+    /// mq.push(2).unwrap();
+    ///
+    /// assert_eq!(mq.peek(), Some(&3));
+    /// ```
+    pub const fn max() -> ArrayMonotonicQueue<T, N> {
+        ArrayMonotonicQueue::with_comparator(Direction::Max)
+    }
+
+    /// Create an empty array monotonic queue whose front holds the minimum.
+    pub const fn min() -> ArrayMonotonicQueue<T, N> {
+        ArrayMonotonicQueue::with_comparator(Direction::Min)
+    }
+}
+
+impl<T, const N: usize, C> ArrayMonotonicQueue<T, N, C> {
+    /// Create an empty array monotonic queue that stores `cmp` and applies it
+    /// on every `push`.
+    pub const fn with_comparator(cmp: C) -> ArrayMonotonicQueue<T, N, C> {
+        ArrayMonotonicQueue {
+            mq: MonotonicQueue::with_array(cmp),
+        }
+    }
+
+    /// Sets the policy applied to equal elements on subsequent pushes.
+    pub fn with_policy(self, policy: Policy) -> ArrayMonotonicQueue<T, N, C> {
+        ArrayMonotonicQueue {
+            mq: self.mq.with_policy(policy),
+        }
+    }
+
+    /// Returns the maximum number of retained elements, `N`.
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the wrapped queue.
+    pub fn into_inner(self) -> MonotonicQueue<T, C, ArrayDeque<T, N>> {
+        self.mq
+    }
+
+    /// Pushes `item` to the back, popping the elements for which
+    /// `is_less(existing_item, &item)` holds, as
+    /// [`MonotonicQueue::push_by`] does.
+    ///
+    /// Returns `Err(item)` if the queue is still full once the dominated
+    /// elements were popped.
+    pub fn push_by<F>(&mut self, item: T, is_less: F) -> Result<(), T>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let (dq, _, policy) = self.mq.parts_mut();
+        push_back(
+            dq,
+            policy,
+            item,
+            |existing_item, item| is_less(existing_item, item),
+            |existing_item, item| is_less(item, existing_item),
+            drop,
+        )
+        .map(drop)
+    }
+}
+
+impl<T, const N: usize, C: Compare<T>> ArrayMonotonicQueue<T, N, C> {
+    /// Pushes `item` to the back, popping the elements it dominates under the
+    /// queue's comparator.
+    ///
+    /// Returns `Err(item)` if the queue is still full once the dominated
+    /// elements were popped.
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        let (dq, cmp, policy) = self.mq.parts_mut();
+        push_back(
+            dq,
+            policy,
+            item,
+            |existing_item, item| cmp.is_less(existing_item, item),
+            |existing_item, item| cmp.is_less(item, existing_item),
+            drop,
+        )
+        .map(drop)
+    }
+}
+
+impl<T, const N: usize, C> Deref for ArrayMonotonicQueue<T, N, C> {
+    type Target = MonotonicQueue<T, C, ArrayDeque<T, N>>;
+
+    fn deref(&self) -> &MonotonicQueue<T, C, ArrayDeque<T, N>> {
+        &self.mq
+    }
+}
+
+impl<T, const N: usize, C> DerefMut for ArrayMonotonicQueue<T, N, C> {
+    fn deref_mut(&mut self) -> &mut MonotonicQueue<T, C, ArrayDeque<T, N>> {
+        &mut self.mq
+    }
+}
+
+impl<T, const N: usize, C: Default> Default for ArrayMonotonicQueue<T, N, C> {
+    fn default() -> ArrayMonotonicQueue<T, N, C> {
+        ArrayMonotonicQueue::with_comparator(C::default())
+    }
+}
+
+impl<T: fmt::Debug, const N: usize, C> fmt::Debug for ArrayMonotonicQueue<T, N, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ArrayMonotonicQueue")
+            .field(&self.mq)
+            .finish()
+    }
+}
+
+/// Pushes every element, panicking once the queue is full, as the wrapped
+/// queue does.
+impl<T, const N: usize, C: Compare<T>> Extend<T> for ArrayMonotonicQueue<T, N, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.mq.extend(iter);
+    }
+}
+
+impl<T, const N: usize, C> IntoIterator for ArrayMonotonicQueue<T, N, C> {
+    type Item = T;
+    type IntoIter = IntoIter<T, ArrayDeque<T, N>>;
+
+    fn into_iter(self) -> IntoIter<T, ArrayDeque<T, N>> {
+        self.mq.into_iter()
+    }
+}
+
+impl<'a, T, const N: usize, C> IntoIterator for &'a ArrayMonotonicQueue<T, N, C> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, ArrayDeque<T, N>>;
+
+    fn into_iter(self) -> Iter<'a, T, ArrayDeque<T, N>> {
+        self.mq.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use crate::{ArrayMonotonicQueue, Policy};
+
+    #[test]
+    fn array_monotonic_queue_wraps_around() {
+        let mut mq: ArrayMonotonicQueue<i32, 3> = ArrayMonotonicQueue::min();
+
+        for n in 0..10 {
+            mq.push(n).unwrap();
+            if mq.len() == mq.capacity() {
+                assert_eq!(mq.pop(), Some(n - 2));
+            }
+        }
+
+        assert_eq!(mq.peek(), Some(&8));
+        assert_eq!(mq.peek_back(), Some(&9));
+    }
+
+    #[test]
+    fn array_monotonic_queue_full() {
+        let mut mq: ArrayMonotonicQueue<i32, 2> =
+            ArrayMonotonicQueue::max().with_policy(Policy::KeepEqualNewest);
+
+        assert_eq!(mq.push(3), Ok(()));
+        assert_eq!(mq.push(2), Ok(()));
+        assert_eq!(mq.push(1), Err(1));
+        assert_eq!(mq.push(2), Ok(()));
+        assert_eq!(mq.get(1), Some(&2));
+
+        let mut empty: ArrayMonotonicQueue<i32, 0> = ArrayMonotonicQueue::max();
+        assert_eq!(empty.push(1), Err(1));
+        assert_eq!(empty.peek(), None);
+    }
+
+    #[test]
+    fn array_monotonic_queue_shares_queue_api() {
+        let mut mq: ArrayMonotonicQueue<i32, 4> = ArrayMonotonicQueue::max();
+        mq.extend_from_slice(&[9, 5, 3]);
+
+        let mut evicted = 0;
+        mq.push_with_evicted(6, |n| evicted += n);
+        assert_eq!(evicted, 8);
+        assert_eq!(mq.push_front(10), Ok(()));
+        assert_eq!(mq.pop_back(), Some(6));
+        mq.extend([7, 2]);
+        assert!(mq.iter().eq(&[10, 9, 7, 2]));
+        assert_eq!(mq.validate(), Ok(()));
+
+        assert_eq!(mq.push(1), Err(1));
+        assert!(mq.into_iter().rev().eq([2, 7, 9, 10]));
+    }
+
+    #[test]
+    fn array_monotonic_queue_drops_elements() {
+        struct Counted<'a>(i32, &'a Cell<usize>);
+
+        impl Drop for Counted<'_> {
+            fn drop(&mut self) {
+                self.1.set(self.1.get() + 1);
+            }
+        }
+
+        let drops = Cell::new(0);
+        {
+            let mut mq =
+                ArrayMonotonicQueue::<_, 4, _>::with_comparator(|n1: &Counted, n2: &Counted| {
+                    n1.0 < n2.0
+                });
+            for n in [4, 1, 3, 2] {
+                assert!(mq.push(Counted(n, &drops)).is_ok());
+            }
+            assert_eq!(drops.get(), 1);
+        }
+
+        assert_eq!(drops.get(), 4);
+    }
+}
@@ -0,0 +1,30 @@
+use crate::Direction;
+
+/// A comparator deciding which elements of a monotonic queue dominate others.
+///
+/// `is_less(n1, n2)` returns true if `n1` is dominated by `n2`, i.e. `n1` can
+/// no longer be the extremum once `n2` has been pushed after it. The front of
+/// the queue therefore holds the greatest element under this comparator.
+///
+/// Implemented for [`Direction`] on `T: Ord`, and for every closure
+/// `Fn(&T, &T) -> bool`, so the closures accepted by `push_by` can be stored
+/// in a queue as well.
+pub trait Compare<T: ?Sized> {
+    /// Returns true if `n1` is dominated by `n2`.
+    fn is_less(&self, n1: &T, n2: &T) -> bool;
+}
+
+impl<T: ?Sized, F> Compare<T> for F
+where
+    F: Fn(&T, &T) -> bool,
+{
+    fn is_less(&self, n1: &T, n2: &T) -> bool {
+        self(n1, n2)
+    }
+}
+
+impl<T: Ord> Compare<T> for Direction {
+    fn is_less(&self, n1: &T, n2: &T) -> bool {
+        Direction::is_less(*self, n1, n2)
+    }
+}
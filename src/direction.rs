@@ -0,0 +1,29 @@
+/// Direction of a monotonic queue, i.e. which extremum sits at its front.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    /// The front holds the maximum; elements decrease from front to back.
+    #[default]
+    Max,
+    /// The front holds the minimum; elements increase from front to back.
+    Min,
+}
+
+impl Direction {
+    /// Returns true if `n1` is dominated by `n2` in this direction, i.e. `n1`
+    /// can no longer be the extremum once `n2` has been pushed after it.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::Direction;
+    ///
+    /// assert!(Direction::Max.is_less(&1, &2));
+    /// assert!(Direction::Min.is_less(&2, &1));
+    /// ```
+    pub fn is_less<T: Ord>(self, n1: &T, n2: &T) -> bool {
+        match self {
+            Direction::Max => n1 < n2,
+            Direction::Min => n1 > n2,
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use core::cmp::Ordering;
+use core::fmt;
+
+#[cfg(feature = "std")]
+use std::collections::VecDeque;
+
+use crate::{Compare, DequeStorage, Direction, MonotonicQueue};
+
+/// Floating point types a [`FloatOrder`] can compare, i.e. `f32` and `f64`.
+pub trait Float: Copy + PartialOrd {
+    /// Returns true if this value is NaN.
+    fn is_nan(self) -> bool;
+
+    /// Returns the IEEE 754 total ordering between `self` and `other`.
+    fn total_cmp(&self, other: &Self) -> Ordering;
+}
+
+impl Float for f32 {
+    fn is_nan(self) -> bool {
+        f32::is_nan(self)
+    }
+
+    fn total_cmp(&self, other: &f32) -> Ordering {
+        f32::total_cmp(self, other)
+    }
+}
+
+impl Float for f64 {
+    fn is_nan(self) -> bool {
+        f64::is_nan(self)
+    }
+
+    fn total_cmp(&self, other: &f64) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+}
+
+/// Policy deciding how NaN is ordered, or whether it is admitted at all.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NanPolicy {
+    /// Order values with [`f64::total_cmp`], which puts positive NaN above
+    /// infinity and negative NaN below negative infinity.
+    #[default]
+    TotalOrder,
+    /// Treat every NaN as greater than any other value.
+    Greatest,
+    /// Treat every NaN as less than any other value.
+    Least,
+    /// Refuse NaN: `try_push` returns a [`NanError`].
+    Reject,
+    /// Ignore NaN: `try_push` drops it without touching the queue.
+    Skip,
+}
+
+/// A comparator totally ordering floats, so NaN cannot break the monotonic
+/// order of a queue.
+///
+/// Under [`NanPolicy::Reject`] and [`NanPolicy::Skip`], NaN is filtered out by
+/// `try_push`; should one reach the comparator anyway, through `push`, it is
+/// ordered as under [`NanPolicy::TotalOrder`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FloatOrder {
+    direction: Direction,
+    nan: NanPolicy,
+}
+
+impl FloatOrder {
+    /// Create a float comparator in `direction`, handling NaN under `nan`.
+    pub fn new(direction: Direction, nan: NanPolicy) -> FloatOrder {
+        FloatOrder { direction, nan }
+    }
+
+    /// Create a float comparator under which the front holds the maximum.
+    pub fn max(nan: NanPolicy) -> FloatOrder {
+        FloatOrder::new(Direction::Max, nan)
+    }
+
+    /// Create a float comparator under which the front holds the minimum.
+    pub fn min(nan: NanPolicy) -> FloatOrder {
+        FloatOrder::new(Direction::Min, nan)
+    }
+
+    /// Returns the direction floats are ordered in.
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Returns the policy NaN is handled under.
+    pub fn nan_policy(&self) -> NanPolicy {
+        self.nan
+    }
+
+    /// Returns the ordering of `n1` and `n2` under the NaN policy.
+    fn cmp<F: Float>(&self, n1: &F, n2: &F) -> Ordering {
+        let nan_ordering = match self.nan {
+            NanPolicy::Greatest => Ordering::Greater,
+            NanPolicy::Least => Ordering::Less,
+            NanPolicy::TotalOrder | NanPolicy::Reject | NanPolicy::Skip => {
+                return n1.total_cmp(n2);
+            }
+        };
+        match (n1.is_nan(), n2.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => nan_ordering,
+            (false, true) => nan_ordering.reverse(),
+            (false, false) => n1.partial_cmp(n2).unwrap_or(Ordering::Equal),
+        }
+    }
+}
+
+impl<F: Float> Compare<F> for FloatOrder {
+    fn is_less(&self, n1: &F, n2: &F) -> bool {
+        match self.direction {
+            Direction::Max => self.cmp(n1, n2) == Ordering::Less,
+            Direction::Min => self.cmp(n1, n2) == Ordering::Greater,
+        }
+    }
+}
+
+/// The error returned when pushing NaN under [`NanPolicy::Reject`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NanError;
+
+impl fmt::Display for NanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("NaN cannot be pushed into this queue")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NanError {}
+
+#[cfg(feature = "std")]
+impl<F: Float> MonotonicQueue<F, FloatOrder> {
+    /// Create an empty float monotonic queue whose front holds the maximum,
+    /// handling NaN under `nan`.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::{MonotonicQueue, NanError, NanPolicy};
+    ///
+    /// let mut mq = MonotonicQueue::max_float(NanPolicy::Reject);
+    /// mq.try_push(1.5).unwrap();
+    ///
+    /// assert_eq!(mq.try_push(f64::NAN), Err(NanError));
+    /// assert_eq!(mq.peek(), Some(&1.5));
+    /// ```
+    pub fn max_float(nan: NanPolicy) -> MonotonicQueue<F, FloatOrder> {
+        MonotonicQueue::with_storage(FloatOrder::max(nan), VecDeque::new())
+    }
+
+    /// Create an empty float monotonic queue whose front holds the minimum,
+    /// handling NaN under `nan`.
+    pub fn min_float(nan: NanPolicy) -> MonotonicQueue<F, FloatOrder> {
+        MonotonicQueue::with_storage(FloatOrder::min(nan), VecDeque::new())
+    }
+}
+
+impl<F: Float, S: DequeStorage<F>> MonotonicQueue<F, FloatOrder, S> {
+    /// Pushes `item` like `push`, unless it is NaN and the queue's NaN policy
+    /// is [`NanPolicy::Reject`], which returns an error, or
+    /// [`NanPolicy::Skip`], which leaves the queue untouched.
+    ///
+    /// # Panics
+    /// Panics if the storage is bounded and full.
+    pub fn try_push(&mut self, item: F) -> Result<(), NanError> {
+        if item.is_nan() {
+            match self.comparator().nan_policy() {
+                NanPolicy::Reject => return Err(NanError),
+                NanPolicy::Skip => return Ok(()),
+                NanPolicy::TotalOrder | NanPolicy::Greatest | NanPolicy::Least => {}
+            }
+        }
+        self.push(item);
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::testing::POLICIES;
+    use crate::{Compare, Direction, FloatOrder, MonotonicQueue, NanError, NanPolicy, Policy};
+
+    const NAN_POLICIES: [NanPolicy; 5] = [
+        NanPolicy::TotalOrder,
+        NanPolicy::Greatest,
+        NanPolicy::Least,
+        NanPolicy::Reject,
+        NanPolicy::Skip,
+    ];
+
+    fn float() -> impl Strategy<Value = f64> {
+        prop_oneof![
+            Just(f64::NAN),
+            Just(-f64::NAN),
+            Just(f64::INFINITY),
+            Just(f64::NEG_INFINITY),
+            Just(0.0),
+            Just(-0.0),
+            -100.0..100.0,
+        ]
+    }
+
+    #[test]
+    fn float_queue_nan_policies() {
+        let values = [1.0, f64::NAN, 3.0, 2.0];
+        let drain = |nan: NanPolicy| {
+            let mut mq = MonotonicQueue::max_float(nan);
+            let errors = values
+                .iter()
+                .filter(|&&n| mq.try_push(n) == Err(NanError))
+                .count();
+            (mq.into_iter().collect::<Vec<f64>>(), errors)
+        };
+
+        let (greatest, _) = drain(NanPolicy::Greatest);
+        assert!(greatest[0].is_nan());
+        assert_eq!(&greatest[1..], &[3.0, 2.0]);
+
+        assert_eq!(drain(NanPolicy::Least), (vec![3.0, 2.0], 0));
+        assert_eq!(drain(NanPolicy::Reject), (vec![3.0, 2.0], 1));
+        assert_eq!(drain(NanPolicy::Skip), (vec![3.0, 2.0], 0));
+    }
+
+    proptest! {
+        #[test]
+        fn float_queue_stays_monotonic(values in prop::collection::vec(float(), 0..64)) {
+            for direction in [Direction::Max, Direction::Min] {
+                for nan in NAN_POLICIES {
+                    for policy in POLICIES {
+                        let cmp = FloatOrder::new(direction, nan);
+                        let mut mq = MonotonicQueue::with_comparator(cmp).with_policy(policy);
+                        for &n in &values {
+                            let pushed = mq.try_push(n);
+                            prop_assert_eq!(pushed.is_err(), nan == NanPolicy::Reject && n.is_nan());
+                        }
+
+                        let retained: Vec<f64> = mq.iter().copied().collect();
+                        for pair in retained.windows(2) {
+                            prop_assert!(!cmp.is_less(&pair[0], &pair[1]));
+                            if policy != Policy::NonStrict {
+                                prop_assert!(cmp.is_less(&pair[1], &pair[0]));
+                            }
+                        }
+                        if matches!(nan, NanPolicy::Reject | NanPolicy::Skip) {
+                            prop_assert!(retained.iter().all(|n| !n.is_nan()));
+                        }
+                        if let Some(front) = retained.first() {
+                            prop_assert!(values.iter().all(|n| !cmp.is_less(front, n)
+                                || matches!(nan, NanPolicy::Reject | NanPolicy::Skip) && n.is_nan()));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use core::fmt;
+use core::ops::Range;
+
+use crate::{DequeStorage, Policy};
+
+/// The error returned when a monotonic queue is found out of order: the
+/// element at `index + 1` dominates the element at `index` under the checked
+/// comparator, or equals it under a policy that does not retain equal
+/// elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InvariantViolation {
+    index: usize,
+}
+
+impl InvariantViolation {
+    /// Returns the index of the first element of the offending pair.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "elements at {} and {} are out of monotonic order",
+            self.index,
+            self.index + 1
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvariantViolation {}
+
+/// Checks the adjacent pairs of `dq` whose first index is in `pairs`, with the
+/// predicates of [`push_back`](crate::storage::push_back).
+pub(crate) fn validate<T, S, F, G>(
+    dq: &S,
+    policy: Policy,
+    pairs: Range<usize>,
+    is_below: F,
+    is_above: G,
+) -> Result<(), InvariantViolation>
+where
+    S: DequeStorage<T>,
+    F: Fn(&T, &T) -> bool,
+    G: Fn(&T, &T) -> bool,
+{
+    for index in pairs.start..pairs.end.min(dq.len().saturating_sub(1)) {
+        if let (Some(older), Some(newer)) = (dq.get(index), dq.get(index + 1)) {
+            let ordered = match policy {
+                Policy::NonStrict => !is_below(older, newer),
+                Policy::KeepEqualOldest | Policy::KeepEqualNewest => is_above(older, newer),
+            };
+            if !ordered {
+                return Err(InvariantViolation { index });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks `dq` after a push at the back: every pair with the `checked`
+/// feature, otherwise the last two pairs when debug assertions are enabled,
+/// which catches a push whose comparator disagrees with the order already
+/// stored.
+///
+/// # Panics
+/// Panics if the checked pairs are out of order.
+pub(crate) fn check_push<T, S, F, G>(dq: &S, policy: Policy, is_below: F, is_above: G)
+where
+    S: DequeStorage<T>,
+    F: Fn(&T, &T) -> bool,
+    G: Fn(&T, &T) -> bool,
+{
+    let len = dq.len();
+    check(dq, policy, len.saturating_sub(3)..len, is_below, is_above);
+}
+
+/// Checks `dq` after a push at the front, as `check_push` does at the back.
+///
+/// # Panics
+/// Panics if the checked pairs are out of order.
+pub(crate) fn check_push_front<T, S, F, G>(dq: &S, policy: Policy, is_below: F, is_above: G)
+where
+    S: DequeStorage<T>,
+    F: Fn(&T, &T) -> bool,
+    G: Fn(&T, &T) -> bool,
+{
+    check(dq, policy, 0..2, is_below, is_above);
+}
+
+/// Checks the pairs in `pairs` when debug assertions are enabled, and every
+/// pair with the `checked` feature.
+fn check<T, S, F, G>(dq: &S, policy: Policy, pairs: Range<usize>, is_below: F, is_above: G)
+where
+    S: DequeStorage<T>,
+    F: Fn(&T, &T) -> bool,
+    G: Fn(&T, &T) -> bool,
+{
+    let pairs = if cfg!(feature = "checked") {
+        0..dq.len()
+    } else if cfg!(debug_assertions) {
+        pairs
+    } else {
+        return;
+    };
+    if let Err(violation) = validate(dq, policy, pairs, is_below, is_above) {
+        panic!("monotonic queue invariant violated: {}", violation);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{ByKey, Compare, Direction, SlidingWindow};
+
+/// What a [`Sliding`] iterator yields before its first window is full.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum WarmUp<T> {
+    /// Yield nothing until `window` elements were consumed, like
+    /// [`slice::windows`].
+    #[default]
+    Skip,
+    /// Yield the extremum of the elements consumed so far.
+    Partial,
+    /// Yield the given value in place of each incomplete window.
+    Pad(T),
+}
+
+/// Extension trait adding sliding window extrema to every [`Iterator`].
+///
+/// # Example
+/// ```
+/// use monotonicqueue::SlidingExt;
+///
+/// let maxima: Vec<i32> = [1, 3, -1, -3, 5, 3].into_iter().sliding_max(3).collect();
+///
+/// assert_eq!(maxima, vec![3, 3, 5, 5]);
+/// ```
+pub trait SlidingExt: Iterator + Sized {
+    /// Yields the maximum of each window of `window` consecutive elements.
+    fn sliding_max(self, window: usize) -> Sliding<Self, Direction> {
+        Sliding::new(self, window, Direction::Max)
+    }
+
+    /// Yields the minimum of each window of `window` consecutive elements.
+    fn sliding_min(self, window: usize) -> Sliding<Self, Direction> {
+        Sliding::new(self, window, Direction::Min)
+    }
+
+    /// Yields the element with the greatest key in each window of `window`
+    /// consecutive elements.
+    fn sliding_max_by_key<K, F>(self, window: usize, key: F) -> Sliding<Self, ByKey<F>>
+    where
+        K: Ord,
+        F: Fn(&Self::Item) -> K,
+    {
+        Sliding::new(self, window, ByKey::max(key))
+    }
+
+    /// Yields the element with the least key in each window of `window`
+    /// consecutive elements.
+    fn sliding_min_by_key<K, F>(self, window: usize, key: F) -> Sliding<Self, ByKey<F>>
+    where
+        K: Ord,
+        F: Fn(&Self::Item) -> K,
+    {
+        Sliding::new(self, window, ByKey::min(key))
+    }
+
+    /// Yields the greatest element under `cmp` in each window of `window`
+    /// consecutive elements.
+    fn sliding_by<C>(self, window: usize, cmp: C) -> Sliding<Self, C>
+    where
+        C: Compare<Self::Item>,
+    {
+        Sliding::new(self, window, cmp)
+    }
+}
+
+impl<I: Iterator> SlidingExt for I {}
+
+/// Lazy iterator over sliding window extrema, created by the methods of
+/// [`SlidingExt`].
+pub struct Sliding<I: Iterator, C> {
+    iter: I,
+    sw: SlidingWindow<I::Item, C>,
+    warm_up: WarmUp<I::Item>,
+}
+
+impl<I: Iterator, C> Sliding<I, C> {
+    fn new(iter: I, window: usize, cmp: C) -> Sliding<I, C> {
+        Sliding {
+            iter,
+            sw: SlidingWindow::with_comparator(window, cmp),
+            warm_up: WarmUp::Skip,
+        }
+    }
+
+    /// Sets what is yielded before the first window is full.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::{SlidingExt, WarmUp};
+    ///
+    /// let partial: Vec<i32> = [2, 1, 3].into_iter().sliding_min(2).warm_up(WarmUp::Partial).collect();
+    /// let padded: Vec<i32> = [2, 1, 3].into_iter().sliding_min(2).warm_up(WarmUp::Pad(0)).collect();
+    ///
+    /// assert_eq!(partial, vec![2, 1, 1]);
+    /// assert_eq!(padded, vec![0, 1, 1]);
+    /// ```
+    pub fn warm_up(mut self, warm_up: WarmUp<I::Item>) -> Sliding<I, C> {
+        self.warm_up = warm_up;
+        self
+    }
+}
+
+impl<I, C> Iterator for Sliding<I, C>
+where
+    I: Iterator,
+    I::Item: Clone,
+    C: Compare<I::Item>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        loop {
+            let item = self.iter.next()?;
+            self.sw.push(item);
+            if self.sw.next_position() >= self.sw.window() {
+                return self.sw.peek().cloned();
+            }
+            match &self.warm_up {
+                WarmUp::Skip => continue,
+                WarmUp::Partial => return self.sw.peek().cloned(),
+                WarmUp::Pad(pad) => return Some(pad.clone()),
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        match self.warm_up {
+            WarmUp::Skip => {
+                let warming = self.sw.window().saturating_sub(self.sw.next_position() + 1);
+                (
+                    lower.saturating_sub(warming),
+                    upper.map(|upper| upper.saturating_sub(warming)),
+                )
+            }
+            WarmUp::Partial | WarmUp::Pad(_) => (lower, upper),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{SlidingExt, WarmUp};
+
+    #[test]
+    fn sliding_extrema() {
+        let values = [1, 3, -1, -3, 5, 3, 6, 7];
+
+        let maxima: Vec<_> = values.into_iter().sliding_max(3).collect();
+        let minima: Vec<_> = values.into_iter().sliding_min(3).collect();
+
+        assert_eq!(maxima, vec![3, 3, 5, 5, 6, 7]);
+        assert_eq!(minima, vec![-1, -3, -3, -3, 3, 3]);
+    }
+
+    #[test]
+    fn sliding_by_key_warm_up() {
+        let trades = [(3, 'a'), (1, 'b'), (2, 'c'), (0, 'd')];
+
+        let cheapest: Vec<_> = trades
+            .into_iter()
+            .sliding_min_by_key(2, |(price, _)| *price)
+            .warm_up(WarmUp::Partial)
+            .map(|(_, tag)| tag)
+            .collect();
+        assert_eq!(cheapest, vec!['a', 'b', 'b', 'd']);
+
+        let sliding = [1, 2, 3].into_iter().sliding_max(5);
+        assert_eq!(sliding.size_hint(), (0, Some(0)));
+        assert_eq!(sliding.count(), 0);
+    }
+}
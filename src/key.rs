@@ -0,0 +1,57 @@
+use crate::{Compare, Direction};
+
+/// A comparator ordering elements by a key projected out of them, mirroring
+/// [`slice::sort_by_key`].
+///
+/// # Example
+/// ```
+/// use monotonicqueue::{ArrayMonotonicQueue, ByKey};
+///
+/// struct Trade {
+///     price: u32,
+///     qty: u32,
+/// }
+///
+/// let mut mq = ArrayMonotonicQueue::<_, 8, _>::with_comparator(ByKey::max(|t: &Trade| t.price));
+/// mq.push(Trade { price: 10, qty: 5 }).ok();
+/// mq.push(Trade { price: 8, qty: 1 }).ok();
+///
+/// assert_eq!(mq.peek().map(|t| t.qty), Some(5));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ByKey<F> {
+    key: F,
+    direction: Direction,
+}
+
+impl<F> ByKey<F> {
+    /// Create a comparator ordering elements by `key` in `direction`.
+    pub fn new(direction: Direction, key: F) -> ByKey<F> {
+        ByKey { key, direction }
+    }
+
+    /// Create a comparator under which the front holds the greatest key.
+    pub fn max(key: F) -> ByKey<F> {
+        ByKey::new(Direction::Max, key)
+    }
+
+    /// Create a comparator under which the front holds the least key.
+    pub fn min(key: F) -> ByKey<F> {
+        ByKey::new(Direction::Min, key)
+    }
+
+    /// Returns the direction keys are ordered in.
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+}
+
+impl<T, K, F> Compare<T> for ByKey<F>
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    fn is_less(&self, n1: &T, n2: &T) -> bool {
+        self.direction.is_less(&(self.key)(n1), &(self.key)(n2))
+    }
+}
@@ -1,95 +1,82 @@
-use std::collections::VecDeque;
+//! Monotonic queues, and the sliding window structures built on them.
+//!
+//! The crate is `no_std` when its default `std` feature is disabled, in which
+//! case [`MonotonicQueue`] has no default storage and is used over an
+//! allocation-free [`ArrayDeque`], most easily as an [`ArrayMonotonicQueue`];
+//! the comparator types remain available.
+//!
+//! The optional `serde` feature makes queues and windows serializable, the
+//! `checked` feature validates the monotonic order after every push, and the
+//! `testing` feature exports `check_conformance`, the test every
+//! [`DequeStorage`] must pass.
+#![cfg_attr(not(feature = "std"), no_std)]
 
-/// Monotonic Queue is a data structure, where the elements from front to end
-/// are either strictly increasing or decreasing. For example, a strictly increasing
-/// monotonic queue will be able to contain `[1, 3, 4, 6, 7]`, but not `[1, 1, 3].
-///
-/// There are two types of monotonoic queue, increasing or decreasing.
-/// - Monotonic increasing queue: to push an element e, starts from the rear element
-///   we pop out element (s >= e) (violation);
-/// - Monotonic decreasing queue: we pop out element s <= e (violation)
-/// - Sometimes we can relax the strict monotonic condition, and can allow the stack
-///   or queue have duplicate value.
-///
-pub struct MonotonicQueue<T> {
-    dq: VecDeque<T>,
-}
+mod array;
+mod compare;
+mod direction;
+mod float;
+mod invariant;
+#[cfg(feature = "std")]
+mod iter;
+mod key;
+#[cfg(feature = "std")]
+mod minmax;
+#[cfg(feature = "std")]
+mod persistent;
+mod policy;
+mod queue;
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "std")]
+mod snapshot;
+#[cfg(feature = "std")]
+mod spsc;
+#[cfg(feature = "std")]
+mod stack;
+mod storage;
+#[cfg(feature = "std")]
+mod swag;
+#[cfg(feature = "std")]
+mod sync;
+#[cfg(any(all(test, feature = "std"), feature = "testing"))]
+mod testing;
+#[cfg(feature = "std")]
+mod timed;
+#[cfg(feature = "std")]
+mod undo;
+#[cfg(feature = "std")]
+mod window;
 
-impl<T> MonotonicQueue<T> {
-    /// Create an empty monotonic queue.
-    ///
-    /// # Example
-    /// ```
-    /// use monotonicqueue::MonotonicQueue;
-    ///
-    /// let mq: MonotonicQueue<i32> = MonotonicQueue::new();
-    /// ```
-    pub fn new() -> MonotonicQueue<T> {
-        MonotonicQueue {
-            dq: VecDeque::new(),
-        }
-    }
-
-    /// Provides a peek to the front element, or None.
-    ///
-    /// # Example
-    /// ```
-    /// use monotonicqueue::MonotonicQueue;
-    ///
-    /// let mut mq = MonotonicQueue::new();
-    ///
-    /// let is_less = |n1: &i32, n2:&i32| n1.lt(n2);
-    /// mq.push_by(1, is_less);
-    /// mq.push_by(2, is_less);
-    ///
-    /// assert_eq!(mq.peek(), Some(&2));
-    /// ```
-    pub fn peek(&self) -> Option<&T> {
-        self.dq.front()
-    }
-
-    pub fn pop(&mut self) -> Option<T> {
-        self.dq.pop_front()
-    }
-
-    pub fn push_by<F>(&mut self, item: T, is_less: F)
-    where
-        F: Fn(&T, &T) -> bool,
-    {
-        while let Some(existing_item) = self.dq.back() {
-            if is_less(existing_item, &item) {
-                self.dq.pop_back();
-            } else {
-                break;
-            }
-        }
-        self.dq.push_back(item);
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::MonotonicQueue;
-
-    #[test]
-    fn monotonic_incresing_queue() {
-        let mut mq = MonotonicQueue::new();
-
-        let is_less = |n1: &i32, n2: &i32| n1.gt(n2);
-        mq.push_by(1, is_less);
-        mq.push_by(2, is_less);
-
-        assert_eq!(mq.peek(), Some(&1));
-    }
-
-    #[test]
-    fn monotonic_decreasing_queue() {
-        let mut mq = MonotonicQueue::new();
-
-        let is_less = |n1: &i32, n2: &i32| n1.lt(n2);
-        mq.push_by(1, is_less);
-        mq.push_by(2, is_less);
-
-        assert_eq!(mq.peek(), Some(&2));
-    }
-}
+pub use array::{ArrayDeque, ArrayMonotonicQueue};
+pub use compare::Compare;
+pub use direction::Direction;
+pub use float::{Float, FloatOrder, NanError, NanPolicy};
+pub use invariant::InvariantViolation;
+#[cfg(feature = "std")]
+pub use iter::{Sliding, SlidingExt, WarmUp};
+pub use key::ByKey;
+#[cfg(feature = "std")]
+pub use minmax::MinMaxQueue;
+#[cfg(feature = "std")]
+pub use persistent::{PersistentIter, PersistentMonotonicQueue};
+pub use policy::Policy;
+pub use queue::{IntoIter, Iter, MonotonicQueue};
+#[cfg(feature = "std")]
+pub use shared::{SharedMonotonicQueue, SharedReader};
+#[cfg(feature = "std")]
+pub use snapshot::{Plain, SnapshotError, SnapshotOrder};
+#[cfg(feature = "std")]
+pub use spsc::{window_channel, WindowConsumer, WindowProducer};
+#[cfg(feature = "std")]
+pub use stack::MonotonicStack;
+pub use storage::DequeStorage;
+#[cfg(feature = "std")]
+pub use swag::{BitOr, Monoid, SlidingAggregator, Sum};
+#[cfg(feature = "testing")]
+pub use testing::check_conformance;
+#[cfg(feature = "std")]
+pub use timed::{TimedWindow, Timestamp};
+#[cfg(feature = "std")]
+pub use undo::{Checkpoint, UndoQueue};
+#[cfg(feature = "std")]
+pub use window::SlidingWindow;
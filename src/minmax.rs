@@ -0,0 +1,245 @@
+use std::ops::Sub;
+
+#[cfg(feature = "serde")]
+use serde::de::Error as _;
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::window::is_expired;
+use crate::MonotonicQueue;
+#[cfg(feature = "serde")]
+use crate::{window::check_positions, Direction, Policy};
+
+/// Min Max Queue keeps both extrema of the last `window` pushed elements.
+///
+/// It maintains a decreasing and an increasing monotonic queue over the same
+/// logical sequence, sharing one position counter so that elements falling out
+/// of the window are evicted from both at once. Each retained element is
+/// stored in the queues it is a candidate for, hence `T: Clone`.
+pub struct MinMaxQueue<T> {
+    max: MonotonicQueue<(usize, T)>,
+    min: MonotonicQueue<(usize, T)>,
+    window: usize,
+    next: usize,
+}
+
+impl<T> MinMaxQueue<T> {
+    /// Create an empty min max queue covering the last `window` elements.
+    ///
+    /// # Panics
+    /// Panics if `window` is zero.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::MinMaxQueue;
+    ///
+    /// let mmq: MinMaxQueue<i32> = MinMaxQueue::new(3);
+    /// ```
+    pub fn new(window: usize) -> MinMaxQueue<T> {
+        assert!(window > 0, "window length must be non-zero");
+        MinMaxQueue {
+            max: MonotonicQueue::new(),
+            min: MonotonicQueue::new(),
+            window,
+            next: 0,
+        }
+    }
+
+    /// Returns the window length.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Returns the logical position the next pushed element will receive.
+    pub fn next_position(&self) -> usize {
+        self.next
+    }
+
+    /// Provides a peek to the maximum of the current window, or None.
+    pub fn max(&self) -> Option<&T> {
+        self.max.peek().map(|(_, item)| item)
+    }
+
+    /// Provides a peek to the minimum of the current window, or None.
+    pub fn min(&self) -> Option<&T> {
+        self.min.peek().map(|(_, item)| item)
+    }
+
+    /// Returns `max - min` over the current window, or None if it is empty.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::MinMaxQueue;
+    ///
+    /// let mut mmq = MinMaxQueue::new(3);
+    /// for n in [4, 9, 6, 7] {
+    ///     mmq.push(n);
+    /// }
+    ///
+    /// assert_eq!(mmq.min(), Some(&6));
+    /// assert_eq!(mmq.max(), Some(&9));
+    /// assert_eq!(mmq.range(), Some(3));
+    /// ```
+    pub fn range(&self) -> Option<T::Output>
+    where
+        T: Clone + Sub,
+    {
+        match (self.max(), self.min()) {
+            (Some(max), Some(min)) => Some(max.clone() - min.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl<T: Ord + Clone> MinMaxQueue<T> {
+    /// Pushes `item` at the next logical position, evicting the elements that
+    /// fell out of the window from both queues and then those dominated by
+    /// `item` in each direction.
+    pub fn push(&mut self, item: T) {
+        let position = self.next;
+        self.next += 1;
+
+        for mq in [&mut self.max, &mut self.min] {
+            while let Some((front, _)) = mq.peek() {
+                if is_expired(*front, position, self.window) {
+                    mq.pop();
+                } else {
+                    break;
+                }
+            }
+        }
+        self.max
+            .push_by((position, item.clone()), |(_, n1), (_, n2)| n1 < n2);
+        self.min
+            .push_by((position, item), |(_, n1), (_, n2)| n1 > n2);
+    }
+}
+
+/// Serializes the window length, the next position and the elements retained
+/// for the maximum and for the minimum, with their positions.
+#[cfg(feature = "serde")]
+impl<T: Serialize> Serialize for MinMaxQueue<T> {
+    fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        let mut state = serializer.serialize_struct("MinMaxQueue", 4)?;
+        state.serialize_field("window", &self.window)?;
+        state.serialize_field("next", &self.next)?;
+        state.serialize_field("max", &self.max.serialize_items())?;
+        state.serialize_field("min", &self.min.serialize_items())?;
+        state.end()
+    }
+}
+
+/// Restores the window as it was serialized, rejecting a snapshot whose
+/// positions are inconsistent or whose elements are out of order.
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de> + Ord> Deserialize<'de> for MinMaxQueue<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<MinMaxQueue<T>, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "MinMaxQueue")]
+        struct Snapshot<T> {
+            window: usize,
+            next: usize,
+            max: Vec<(usize, T)>,
+            min: Vec<(usize, T)>,
+        }
+
+        let snapshot = Snapshot::deserialize(deserializer)?;
+        let restore = |items, is_less: fn(&T, &T) -> bool| {
+            let mq = MonotonicQueue::restore(Direction::Max, Policy::NonStrict, items)
+                .ok_or("snapshot overflows the queue storage")?;
+            check_positions(&mq, snapshot.window, snapshot.next)?;
+            match mq.validate_by(|(_, n1), (_, n2)| is_less(n1, n2)) {
+                Ok(()) => Ok(mq),
+                Err(_) => Err("snapshot elements are out of monotonic order"),
+            }
+        };
+        let max = restore(snapshot.max, |n1, n2| n1 < n2).map_err(D::Error::custom)?;
+        let min = restore(snapshot.min, |n1, n2| n1 > n2).map_err(D::Error::custom)?;
+        // Both queues retain the newest element, at the same position.
+        if max.peek_back() != min.peek_back() {
+            return Err(D::Error::custom(
+                "snapshot queues disagree on the newest element",
+            ));
+        }
+        if let (Some((_, max)), Some((_, min))) = (max.peek(), min.peek()) {
+            if max < min {
+                return Err(D::Error::custom(
+                    "snapshot maximum is less than its minimum",
+                ));
+            }
+        }
+        Ok(MinMaxQueue {
+            max,
+            min,
+            window: snapshot.window,
+            next: snapshot.next,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::MinMaxQueue;
+
+    #[test]
+    fn min_max_queue_range() {
+        let mut mmq = MinMaxQueue::new(3);
+
+        let mut ranges = Vec::new();
+        for n in [1, 3, -1, -3, 5, 3, 6, 7] {
+            mmq.push(n);
+            ranges.push(mmq.range().unwrap());
+        }
+
+        assert_eq!(ranges, vec![0, 2, 4, 6, 8, 8, 3, 4]);
+    }
+
+    #[test]
+    fn min_max_queue_evicts_both() {
+        let mut mmq = MinMaxQueue::new(2);
+        assert_eq!(mmq.range(), None);
+
+        mmq.push(5);
+        mmq.push(1);
+        mmq.push(3);
+
+        assert_eq!(mmq.max(), Some(&3));
+        assert_eq!(mmq.min(), Some(&1));
+        assert_eq!(mmq.next_position(), 3);
+    }
+
+    #[test]
+    fn min_max_queue_unbounded() {
+        let mut mmq = MinMaxQueue::new(usize::MAX);
+        for n in [5, 9, 1] {
+            mmq.push(n);
+        }
+
+        assert_eq!((mmq.min(), mmq.max()), (Some(&1), Some(&9)));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn min_max_queue_serde() {
+        let mut mmq = MinMaxQueue::new(3);
+        for n in [5, 1, 4, 2] {
+            mmq.push(n);
+        }
+
+        let json = serde_json::to_string(&mmq).unwrap();
+        let mut restored: MinMaxQueue<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!((restored.max(), restored.min()), (Some(&4), Some(&1)));
+        restored.push(3);
+        assert_eq!((restored.max(), restored.min()), (Some(&4), Some(&2)));
+
+        for corrupted in [
+            r#"{"window":3,"next":4,"max":[[2,4],[3,2]],"min":[[2,4],[3,2]]}"#,
+            r#"{"window":3,"next":4,"max":[[3,1]],"min":[[3,9]]}"#,
+            r#"{"window":3,"next":4,"max":[[3,2]],"min":[]}"#,
+        ] {
+            assert!(serde_json::from_str::<MinMaxQueue<i32>>(corrupted).is_err());
+        }
+    }
+}
@@ -0,0 +1,588 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::storage::push_back;
+use crate::{Compare, DequeStorage, Direction, Policy};
+
+#[derive(Clone)]
+struct Node<T> {
+    item: T,
+    left: Tree<T>,
+    right: Tree<T>,
+    len: usize,
+    height: u8,
+}
+
+type Tree<T> = Option<Rc<Node<T>>>;
+
+fn len<T>(tree: &Tree<T>) -> usize {
+    tree.as_ref().map_or(0, |node| node.len)
+}
+
+fn height<T>(tree: &Tree<T>) -> u8 {
+    tree.as_ref().map_or(0, |node| node.height)
+}
+
+impl<T> Node<T> {
+    fn leaf(item: T) -> Node<T> {
+        Node {
+            item,
+            left: None,
+            right: None,
+            len: 1,
+            height: 1,
+        }
+    }
+
+    /// Returns the subtree on the side of the first element, or of the last.
+    fn side(&mut self, first: bool) -> &mut Tree<T> {
+        if first {
+            &mut self.left
+        } else {
+            &mut self.right
+        }
+    }
+
+    /// Recomputes the length and the height from the subtrees.
+    fn update(mut self) -> Node<T> {
+        self.len = len(&self.left) + 1 + len(&self.right);
+        self.height = height(&self.left).max(height(&self.right)) + 1;
+        self
+    }
+}
+
+/// Takes the node out of `node`, cloning it only if another version shares it.
+fn take<T: Clone>(node: Rc<Node<T>>) -> Node<T> {
+    Rc::unwrap_or_clone(node)
+}
+
+fn rotate_right<T: Clone>(mut node: Node<T>) -> Node<T> {
+    let Some(left) = node.left.take() else {
+        return node.update();
+    };
+    let mut left = take(left);
+    node.left = left.right.take();
+    left.right = Some(Rc::new(node.update()));
+    left.update()
+}
+
+fn rotate_left<T: Clone>(mut node: Node<T>) -> Node<T> {
+    let Some(right) = node.right.take() else {
+        return node.update();
+    };
+    let mut right = take(right);
+    node.right = right.left.take();
+    right.left = Some(Rc::new(node.update()));
+    right.update()
+}
+
+/// Restores the balance of `node`, whose subtrees are balanced and differ in
+/// height by two at most.
+fn balance<T: Clone>(mut node: Node<T>) -> Node<T> {
+    let (left, right) = (height(&node.left), height(&node.right));
+    if left > right + 1 {
+        if node
+            .left
+            .as_ref()
+            .is_some_and(|left| height(&left.left) < height(&left.right))
+        {
+            node.left = node
+                .left
+                .take()
+                .map(|left| Rc::new(rotate_left(take(left))));
+        }
+        rotate_right(node)
+    } else if right > left + 1 {
+        if node
+            .right
+            .as_ref()
+            .is_some_and(|right| height(&right.right) < height(&right.left))
+        {
+            node.right = node
+                .right
+                .take()
+                .map(|right| Rc::new(rotate_right(take(right))));
+        }
+        rotate_left(node)
+    } else {
+        node.update()
+    }
+}
+
+/// Inserts `item` before the first element of `tree`, or after its last.
+fn insert<T: Clone>(tree: Tree<T>, item: T, first: bool) -> Node<T> {
+    match tree {
+        None => Node::leaf(item),
+        Some(node) => {
+            let mut node = take(node);
+            let side = node.side(first).take();
+            *node.side(first) = Some(Rc::new(insert(side, item, first)));
+            balance(node)
+        }
+    }
+}
+
+/// Removes the first element of `node`, or its last, returning it along with
+/// the remaining tree.
+fn remove<T: Clone>(node: Rc<Node<T>>, first: bool) -> (T, Tree<T>) {
+    let mut node = take(node);
+    match node.side(first).take() {
+        None => {
+            let rest = node.side(!first).take();
+            (node.item, rest)
+        }
+        Some(side) => {
+            let (item, side) = remove(side, first);
+            *node.side(first) = side;
+            (item, Some(Rc::new(balance(node))))
+        }
+    }
+}
+
+/// Deque of a shared AVL tree ordered by position, so that both ends, and any
+/// index, are found in O(log n).
+///
+/// Cloning is O(1): versions share every node they have in common. A push or a
+/// pop copies only the nodes on the path to the end it changes, reusing those
+/// no other version shares, so it takes O(log n) time and memory in the worst
+/// case, however versions branch.
+pub(crate) struct PersistentDeque<T> {
+    root: Tree<T>,
+}
+
+impl<T> PersistentDeque<T> {
+    pub(crate) fn new() -> PersistentDeque<T> {
+        PersistentDeque { root: None }
+    }
+
+    fn len(&self) -> usize {
+        len(&self.root)
+    }
+
+    fn nth(&self, mut index: usize) -> Option<&T> {
+        let mut node = self.root.as_deref()?;
+        loop {
+            let left = len(&node.left);
+            match index.cmp(&left) {
+                Ordering::Less => node = node.left.as_deref()?,
+                Ordering::Equal => return Some(&node.item),
+                Ordering::Greater => {
+                    index -= left + 1;
+                    node = node.right.as_deref()?;
+                }
+            }
+        }
+    }
+
+    /// Returns the first element, or the last.
+    fn end(&self, first: bool) -> Option<&T> {
+        let mut node = self.root.as_deref()?;
+        loop {
+            let side = if first { &node.left } else { &node.right };
+            match side.as_deref() {
+                Some(next) => node = next,
+                None => return Some(&node.item),
+            }
+        }
+    }
+}
+
+impl<T: Clone> PersistentDeque<T> {
+    fn insert(&mut self, item: T, first: bool) {
+        self.root = Some(Rc::new(insert(self.root.take(), item, first)));
+    }
+
+    fn remove(&mut self, first: bool) -> Option<T> {
+        let (item, rest) = remove(self.root.take()?, first);
+        self.root = rest;
+        Some(item)
+    }
+}
+
+impl<T> Clone for PersistentDeque<T> {
+    fn clone(&self) -> PersistentDeque<T> {
+        PersistentDeque {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<T: Clone> DequeStorage<T> for PersistentDeque<T> {
+    fn len(&self) -> usize {
+        PersistentDeque::len(self)
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        self.nth(index)
+    }
+
+    fn push_back(&mut self, item: T) -> Result<(), T> {
+        self.insert(item, false);
+        Ok(())
+    }
+
+    fn push_front(&mut self, item: T) -> Result<(), T> {
+        self.insert(item, true);
+        Ok(())
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        self.remove(false)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        self.remove(true)
+    }
+
+    fn front(&self) -> Option<&T> {
+        self.end(true)
+    }
+
+    fn back(&self) -> Option<&T> {
+        self.end(false)
+    }
+}
+
+/// Persistent Monotonic Queue is an immutable [`MonotonicQueue`] whose `push`
+/// and `pop` return a new version, leaving the original untouched.
+///
+/// Versions share the elements they have in common: `push` and `pop` take
+/// O(log n) time in the worst case, copying and cloning O(log n) of the
+/// elements, so retaining many versions costs memory proportional to the
+/// changes between them rather than to their length, up to that logarithmic
+/// factor. Elements should hence be cheap to clone.
+///
+/// [`MonotonicQueue`]: crate::MonotonicQueue
+///
+/// # Example
+/// ```
+/// use monotonicqueue::PersistentMonotonicQueue;
+///
+/// let v1 = PersistentMonotonicQueue::max().push(5).push(3);
+/// let v2 = v1.push(4);
+/// let (v3, max) = v2.pop();
+///
+/// assert!(v1.iter().eq(&[5, 3]));
+/// assert!(v2.iter().eq(&[5, 4]));
+/// assert_eq!(max, Some(5));
+/// assert!(v3.iter().eq(&[4]));
+/// ```
+pub struct PersistentMonotonicQueue<T, C = Direction> {
+    dq: PersistentDeque<T>,
+    cmp: C,
+    policy: Policy,
+}
+
+impl<T> PersistentMonotonicQueue<T> {
+    /// Create an empty persistent monotonic queue whose front holds the
+    /// maximum.
+    pub fn max() -> PersistentMonotonicQueue<T> {
+        PersistentMonotonicQueue::with_comparator(Direction::Max)
+    }
+
+    /// Create an empty persistent monotonic queue whose front holds the
+    /// minimum.
+    pub fn min() -> PersistentMonotonicQueue<T> {
+        PersistentMonotonicQueue::with_comparator(Direction::Min)
+    }
+}
+
+impl<T, C> PersistentMonotonicQueue<T, C> {
+    /// Create an empty persistent monotonic queue that stores `cmp` and
+    /// applies it on every `push`.
+    pub fn with_comparator(cmp: C) -> PersistentMonotonicQueue<T, C> {
+        PersistentMonotonicQueue {
+            dq: PersistentDeque::new(),
+            cmp,
+            policy: Policy::NonStrict,
+        }
+    }
+
+    /// Sets the policy applied to equal elements on subsequent pushes.
+    pub fn with_policy(mut self, policy: Policy) -> PersistentMonotonicQueue<T, C> {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the comparator `push` applies.
+    pub fn comparator(&self) -> &C {
+        &self.cmp
+    }
+
+    /// Returns the policy applied to equal elements.
+    pub fn policy(&self) -> Policy {
+        self.policy
+    }
+
+    /// Returns the number of retained elements.
+    pub fn len(&self) -> usize {
+        self.dq.len()
+    }
+
+    /// Returns true if the queue retains no element.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Provides a peek to the front element, or None.
+    pub fn peek(&self) -> Option<&T> {
+        self.dq.end(true)
+    }
+
+    /// Provides a peek to the back element, or None.
+    pub fn peek_back(&self) -> Option<&T> {
+        self.dq.end(false)
+    }
+
+    /// Returns a front-to-back iterator over the retained elements.
+    pub fn iter(&self) -> PersistentIter<'_, T> {
+        let mut iter = PersistentIter {
+            stack: Vec::with_capacity(height(&self.dq.root).into()),
+        };
+        iter.descend(self.dq.root.as_deref());
+        iter
+    }
+}
+
+impl<T: Clone, C: Clone> PersistentMonotonicQueue<T, C> {
+    /// Returns the version without the front element, along with that
+    /// element, or None if empty.
+    pub fn pop(&self) -> (PersistentMonotonicQueue<T, C>, Option<T>) {
+        let mut next = self.clone();
+        let item = next.dq.pop_front();
+        (next, item)
+    }
+
+    /// Returns the version with `item` pushed to the back, popping the
+    /// elements for which `is_less(existing_item, &item)` holds, as
+    /// [`MonotonicQueue::push_by`](crate::MonotonicQueue::push_by) does.
+    pub fn push_by<F>(&self, item: T, is_less: F) -> PersistentMonotonicQueue<T, C>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let mut next = self.clone();
+        push_back(
+            &mut next.dq,
+            next.policy,
+            item,
+            |existing_item, item| is_less(existing_item, item),
+            |existing_item, item| is_less(item, existing_item),
+            drop,
+        )
+        .ok();
+        next
+    }
+}
+
+impl<T: Clone, C: Clone + Compare<T>> PersistentMonotonicQueue<T, C> {
+    /// Returns the version with `item` pushed to the back, popping the
+    /// elements it dominates under the queue's comparator.
+    pub fn push(&self, item: T) -> PersistentMonotonicQueue<T, C> {
+        let mut next = self.clone();
+        let cmp = &self.cmp;
+        push_back(
+            &mut next.dq,
+            next.policy,
+            item,
+            |existing_item, item| cmp.is_less(existing_item, item),
+            |existing_item, item| cmp.is_less(item, existing_item),
+            drop,
+        )
+        .ok();
+        next
+    }
+}
+
+impl<T, C: Clone> Clone for PersistentMonotonicQueue<T, C> {
+    fn clone(&self) -> PersistentMonotonicQueue<T, C> {
+        PersistentMonotonicQueue {
+            dq: self.dq.clone(),
+            cmp: self.cmp.clone(),
+            policy: self.policy,
+        }
+    }
+}
+
+impl<T, C: Default> Default for PersistentMonotonicQueue<T, C> {
+    fn default() -> PersistentMonotonicQueue<T, C> {
+        PersistentMonotonicQueue::with_comparator(C::default())
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for PersistentMonotonicQueue<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PersistentMonotonicQueue")
+            .field("dq", &DebugItems(self))
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Formats the elements of a queue as a list.
+struct DebugItems<'a, T, C>(&'a PersistentMonotonicQueue<T, C>);
+
+impl<T: fmt::Debug, C> fmt::Debug for DebugItems<'_, T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.0.iter()).finish()
+    }
+}
+
+/// Comparators are not compared, as closures cannot be.
+impl<T: PartialEq, C> PartialEq for PersistentMonotonicQueue<T, C> {
+    fn eq(&self, other: &PersistentMonotonicQueue<T, C>) -> bool {
+        self.policy == other.policy && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, C> Eq for PersistentMonotonicQueue<T, C> {}
+
+impl<'a, T, C> IntoIterator for &'a PersistentMonotonicQueue<T, C> {
+    type Item = &'a T;
+    type IntoIter = PersistentIter<'a, T>;
+
+    fn into_iter(self) -> PersistentIter<'a, T> {
+        self.iter()
+    }
+}
+
+/// A front-to-back iterator over the elements of a
+/// [`PersistentMonotonicQueue`].
+pub struct PersistentIter<'a, T> {
+    /// The nodes whose element and right subtree are yet to be visited.
+    stack: Vec<&'a Node<T>>,
+}
+
+impl<'a, T> PersistentIter<'a, T> {
+    /// Stacks the nodes from `node` down to its first element.
+    fn descend(&mut self, mut node: Option<&'a Node<T>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, T> Iterator for PersistentIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        self.descend(node.right.as_deref());
+        Some(&node.item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use crate::testing::{xorshift, POLICIES};
+    use crate::{ByKey, Direction, MonotonicQueue, PersistentMonotonicQueue};
+
+    thread_local! {
+        static CLONES: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// An element counting its clones, each node a version copies cloning one.
+    #[derive(Debug, PartialEq)]
+    struct Counted(u32);
+
+    impl Clone for Counted {
+        fn clone(&self) -> Counted {
+            CLONES.with(|clones| clones.set(clones.get() + 1));
+            Counted(self.0)
+        }
+    }
+
+    #[test]
+    fn persistent_queue_matches_monotonic_queue() {
+        let mut seed = 0x0bad_cafe_u32;
+        for direction in [Direction::Max, Direction::Min] {
+            for policy in POLICIES {
+                let key: fn(&(u8, usize)) -> u8 = |(value, _)| *value;
+                let cmp = ByKey::new(direction, key);
+                let mut versions = vec![(
+                    PersistentMonotonicQueue::with_comparator(cmp).with_policy(policy),
+                    MonotonicQueue::with_comparator(cmp).with_policy(policy),
+                )];
+
+                for tag in 0..3000 {
+                    let seed = xorshift(&mut seed);
+                    let (pq, mq) = &versions[seed as usize % versions.len()];
+                    let (mut pq, mut mq) = (pq.clone(), mq.clone());
+                    match seed % 3 {
+                        0 => {
+                            let popped;
+                            (pq, popped) = pq.pop();
+                            assert_eq!(popped, mq.pop());
+                        }
+                        _ => {
+                            let item = ((seed >> 8) as u8 % 6, tag);
+                            pq = pq.push(item);
+                            mq.push(item);
+                        }
+                    }
+                    assert!(pq.iter().eq(mq.iter()));
+                    assert_eq!(pq.len(), mq.len());
+                    assert_eq!(pq.peek(), mq.peek());
+                    assert_eq!(pq.peek_back(), mq.peek_back());
+                    versions.push((pq, mq));
+                }
+
+                for (pq, mq) in &versions {
+                    assert!(pq.iter().eq(mq.iter()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "checked", ignore = "validating every push is quadratic")]
+    fn persistent_queue_branches_in_bounded_time() {
+        let mut base =
+            PersistentMonotonicQueue::with_comparator(|n1: &Counted, n2: &Counted| n1.0 > n2.0);
+        for n in 0..100_000 {
+            base = base.push(Counted(n));
+        }
+
+        CLONES.with(|clones| clones.set(0));
+        let mut versions = Vec::new();
+        for n in 0..1000 {
+            let (popped, front) = base.pop();
+            assert_eq!(front, Some(Counted(0)));
+            versions.push(popped);
+            versions.push(base.push(Counted(100_000 + n)));
+            // Pops the back before pushing.
+            versions.push(base.push(Counted(99_998)));
+        }
+
+        // Every version copies at most two paths of a tree of height 24 or
+        // less, where sharing the base's structure would copy all of it.
+        assert!(CLONES.with(Cell::get) <= versions.len() * 2 * 24);
+        for (n, version) in versions.chunks(3).enumerate() {
+            assert_eq!(version[0].peek(), Some(&Counted(1)));
+            assert_eq!(version[1].peek_back(), Some(&Counted(100_000 + n as u32)));
+            assert_eq!(version[2].len(), 100_000);
+            assert_eq!(version[2].peek_back(), Some(&Counted(99_998)));
+        }
+        assert_eq!(base.len(), 100_000);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "checked", ignore = "validating every push is quadratic")]
+    fn persistent_queue_drops_long_lists() {
+        let mut pq = PersistentMonotonicQueue::min();
+        for n in 0..200_000 {
+            pq = pq.push(n);
+        }
+        let (pq, front) = pq.pop();
+
+        assert_eq!(front, Some(0));
+        assert_eq!(pq.len(), 199_999);
+        assert_eq!(
+            format!("{:?}", PersistentMonotonicQueue::max().push(2).push(1)),
+            "PersistentMonotonicQueue { dq: [2, 1], policy: NonStrict, .. }"
+        );
+    }
+}
@@ -0,0 +1,22 @@
+/// Policy deciding what happens to equal elements pushed into a monotonic
+/// queue.
+///
+/// Two elements are equal when neither is less than the other under the
+/// comparator used for the push.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Policy {
+    /// Equal elements are all retained, in push order. The queue is only
+    /// monotonic in the non-strict sense, e.g. `[3, 2, 2, 1]`.
+    ///
+    /// This is what windowed queues need: when the oldest of several equal
+    /// extrema expires, the newer ones are still there to take its place.
+    #[default]
+    NonStrict,
+    /// The queue is strictly monotonic, and of several equal elements the
+    /// oldest is kept: an element equal to the back is not stored.
+    KeepEqualOldest,
+    /// The queue is strictly monotonic, and of several equal elements the
+    /// newest is kept: an element equal to the back evicts it.
+    KeepEqualNewest,
+}
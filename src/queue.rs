@@ -0,0 +1,1268 @@
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+#[cfg(feature = "std")]
+use std::collections::VecDeque;
+
+#[cfg(all(feature = "serde", feature = "std"))]
+use serde::de::Error as _;
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+#[cfg(all(feature = "serde", feature = "std"))]
+use serde::{Deserialize, Deserializer};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+
+#[cfg(feature = "std")]
+use crate::ByKey;
+use crate::{invariant, storage};
+use crate::{ArrayDeque, Compare, DequeStorage, Direction, InvariantViolation, Policy};
+
+/// Monotonic Queue is a data structure, where the elements from front to end
+/// are either strictly increasing or decreasing. For example, a strictly increasing
+/// monotonic queue will be able to contain `[1, 3, 4, 6, 7]`, but not `[1, 1, 3].
+///
+/// There are two types of monotonoic queue, increasing or decreasing.
+/// - Monotonic increasing queue: to push an element e, starts from the rear element
+///   we pop out element (s >= e) (violation);
+/// - Monotonic decreasing queue: we pop out element s <= e (violation)
+/// - Sometimes we can relax the strict monotonic condition, and can allow the stack
+///   or queue have duplicate value. Which of these the queue does is chosen by
+///   its [`Policy`].
+///
+/// Elements are stored in a [`VecDeque`] unless another [`DequeStorage`] is
+/// given to `with_storage`. Without the `std` feature, there is no default
+/// storage nor comparator, and every queue is created by `with_storage`.
+///
+#[derive(Clone)]
+pub struct MonotonicQueue<
+    T,
+    #[cfg(feature = "std")] C = Direction,
+    #[cfg(feature = "std")] S = VecDeque<T>,
+    #[cfg(not(feature = "std"))] C,
+    #[cfg(not(feature = "std"))] S,
+> {
+    dq: S,
+    cmp: C,
+    policy: Policy,
+    marker: PhantomData<T>,
+}
+
+#[cfg(feature = "std")]
+impl<T> MonotonicQueue<T> {
+    /// Create an empty monotonic queue.
+    ///
+    /// The queue's direction is [`Direction::Max`], which only matters to
+    /// `push`; `push_by` always applies the closure it is given.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::MonotonicQueue;
+    ///
+    /// let mq: MonotonicQueue<i32> = MonotonicQueue::new();
+    /// ```
+    pub fn new() -> MonotonicQueue<T> {
+        MonotonicQueue::with_direction(Direction::Max)
+    }
+
+    /// Create an empty monotonic queue whose front holds the maximum.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::MonotonicQueue;
+    ///
+    /// let mut mq = MonotonicQueue::max();
+    /// mq.push(1);
+    /// mq.push(3);
+    /// mq.push(2);
+    ///
+    /// assert_eq!(mq.peek(), Some(&3));
+    /// ```
+    pub fn max() -> MonotonicQueue<T> {
+        MonotonicQueue::with_direction(Direction::Max)
+    }
+
+    /// Create an empty monotonic queue whose front holds the minimum.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::MonotonicQueue;
+    ///
+    /// let mut mq = MonotonicQueue::min();
+    /// mq.push(2);
+    /// mq.push(1);
+    /// mq.push(3);
+    ///
+    /// assert_eq!(mq.peek(), Some(&1));
+    /// ```
+    pub fn min() -> MonotonicQueue<T> {
+        MonotonicQueue::with_direction(Direction::Min)
+    }
+
+    /// Create an empty monotonic queue with the given direction.
+    pub fn with_direction(direction: Direction) -> MonotonicQueue<T> {
+        MonotonicQueue::with_comparator(direction)
+    }
+}
+
+impl<T, S: DequeStorage<T>> MonotonicQueue<T, Direction, S> {
+    /// Returns the direction `push` maintains the queue in.
+    pub fn direction(&self) -> Direction {
+        self.cmp
+    }
+
+    /// Pushes `item` to the back, popping the elements whose key is dominated
+    /// by `key(&item)` in the queue's direction.
+    ///
+    /// The key of `item` is computed once, while each existing element's key
+    /// is computed when it is compared against it.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use monotonicqueue::MonotonicQueue;
+    ///
+    /// let mut mq = MonotonicQueue::min();
+    /// mq.push_by_key((3, 'a'), |(price, _)| *price);
+    /// mq.push_by_key((5, 'b'), |(price, _)| *price);
+    ///
+    /// assert_eq!(mq.peek(), Some(&(3, 'a')));
+    /// # }
+    /// ```
+    pub fn push_by_key<K, F>(&mut self, item: T, key: F)
+    where
+        K: Ord,
+        F: Fn(&T) -> K,
+    {
+        let direction = self.cmp;
+        let item_key = key(&item);
+        push_back(
+            &mut self.dq,
+            self.policy,
+            item,
+            |existing_item, _| direction.is_less(&key(existing_item), &item_key),
+            |existing_item, _| direction.is_less(&item_key, &key(existing_item)),
+            drop,
+        );
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, C> MonotonicQueue<T, C> {
+    /// Create an empty monotonic queue that stores `cmp` and applies it on
+    /// every `push`.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::MonotonicQueue;
+    ///
+    /// let mut mq = MonotonicQueue::with_comparator(|n1: &i32, n2: &i32| n1.abs() < n2.abs());
+    /// mq.push(-3);
+    /// mq.push(2);
+    ///
+    /// assert_eq!(mq.peek(), Some(&-3));
+    /// ```
+    pub fn with_comparator(cmp: C) -> MonotonicQueue<T, C> {
+        MonotonicQueue::with_storage(cmp, VecDeque::new())
+    }
+
+    /// Returns a pair of slices which contain, in order, the retained
+    /// elements front to back, as [`VecDeque::as_slices`] does.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        self.dq.as_slices()
+    }
+}
+
+impl<T, C, S: DequeStorage<T>> MonotonicQueue<T, C, S> {
+    /// Create an empty monotonic queue that stores `cmp` and keeps its
+    /// elements in `storage`.
+    ///
+    /// # Panics
+    /// Panics if `storage` is not empty.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::{ArrayDeque, Direction, MonotonicQueue};
+    ///
+    /// let mut mq = MonotonicQueue::with_storage(Direction::Min, ArrayDeque::<i32, 8>::new());
+    /// mq.extend([3, 1, 2]);
+    ///
+    /// assert_eq!(mq.peek(), Some(&1));
+    /// ```
+    pub fn with_storage(cmp: C, storage: S) -> MonotonicQueue<T, C, S> {
+        assert!(storage.is_empty(), "storage must be empty");
+        MonotonicQueue {
+            dq: storage,
+            cmp,
+            policy: Policy::NonStrict,
+            marker: PhantomData,
+        }
+    }
+
+    /// Sets the policy applied to equal elements on subsequent pushes.
+    ///
+    /// Meant to be chained onto a constructor: elements already in the queue
+    /// are not re-examined.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use monotonicqueue::{MonotonicQueue, Policy};
+    ///
+    /// let mut mq = MonotonicQueue::max().with_policy(Policy::KeepEqualNewest);
+    /// mq.push(2);
+    /// mq.push(2);
+    ///
+    /// assert_eq!(mq.pop(), Some(2));
+    /// assert_eq!(mq.pop(), None);
+    /// # }
+    /// ```
+    pub fn with_policy(mut self, policy: Policy) -> MonotonicQueue<T, C, S> {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the comparator `push` applies.
+    pub fn comparator(&self) -> &C {
+        &self.cmp
+    }
+
+    /// Returns the policy applied to equal elements.
+    pub fn policy(&self) -> Policy {
+        self.policy
+    }
+
+    /// Provides a peek to the front element, or None.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use monotonicqueue::MonotonicQueue;
+    ///
+    /// let mut mq = MonotonicQueue::new();
+    ///
+    /// let is_less = |n1: &i32, n2:&i32| n1.lt(n2);
+    /// mq.push_by(1, is_less);
+    /// mq.push_by(2, is_less);
+    ///
+    /// assert_eq!(mq.peek(), Some(&2));
+    /// # }
+    /// ```
+    pub fn peek(&self) -> Option<&T> {
+        self.dq.front()
+    }
+
+    /// Provides a peek to the back element, i.e. the most recently pushed
+    /// element still retained, or None.
+    pub fn peek_back(&self) -> Option<&T> {
+        self.dq.back()
+    }
+
+    /// Returns the element at index `index` from the front, or None if it is
+    /// out of bounds.
+    ///
+    /// Index `0` is the extremum, index `1` the extremum once it is popped,
+    /// and so on.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use monotonicqueue::MonotonicQueue;
+    ///
+    /// let mut mq = MonotonicQueue::max();
+    /// mq.extend([5, 2, 4, 1]);
+    ///
+    /// assert_eq!(mq.get(1), Some(&4));
+    /// assert_eq!(mq.get(3), None);
+    /// # }
+    /// ```
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.dq.get(index)
+    }
+
+    /// Returns the number of retained elements.
+    pub fn len(&self) -> usize {
+        self.dq.len()
+    }
+
+    /// Returns true if the queue retains no element.
+    pub fn is_empty(&self) -> bool {
+        self.dq.is_empty()
+    }
+
+    /// Returns a front-to-back iterator over the retained elements.
+    ///
+    /// As long as every push used the same comparator, the iteration order is
+    /// monotonic under it: each element is not less than the next one, and
+    /// under a strict policy no two elements are equal.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use monotonicqueue::MonotonicQueue;
+    ///
+    /// let mut mq = MonotonicQueue::min();
+    /// mq.extend([3, 1, 4, 1, 5]);
+    ///
+    /// assert!(mq.iter().eq(&[1, 1, 5]));
+    /// assert!(mq.iter().rev().eq(&[5, 1, 1]));
+    /// # }
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T, S> {
+        Iter {
+            dq: &self.dq,
+            front: 0,
+            back: self.dq.len(),
+            marker: PhantomData,
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.dq.pop_front()
+    }
+
+    /// Returns the storage along with the comparator and the policy, for the
+    /// wrappers driving the storage themselves.
+    pub(crate) fn parts_mut(&mut self) -> (&mut S, &C, Policy) {
+        (&mut self.dq, &self.cmp, self.policy)
+    }
+
+    /// Removes the back element, the most recent one retained, and returns
+    /// it, or None if empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.dq.pop_back()
+    }
+
+    /// Pushes `item` to the back, popping the elements for which
+    /// `is_less(existing_item, &item)` holds. Equal elements are then handled
+    /// according to the queue's policy, which under
+    /// [`Policy::KeepEqualOldest`] may leave `item` unstored.
+    ///
+    /// The stored comparator is ignored, which makes this an escape hatch for
+    /// one-off pushes; mixing comparators can break the monotonic order.
+    ///
+    /// # Panics
+    /// Panics if the storage is bounded and full.
+    pub fn push_by<F>(&mut self, item: T, is_less: F)
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        push_back(
+            &mut self.dq,
+            self.policy,
+            item,
+            |existing_item, item| is_less(existing_item, item),
+            |existing_item, item| is_less(item, existing_item),
+            drop,
+        );
+    }
+
+    /// Pushes `item` like `push_by`, handing every element it evicts to
+    /// `evicted`, back to front. Under [`Policy::KeepEqualOldest`], a refused
+    /// `item` is handed over as well.
+    ///
+    /// # Panics
+    /// Panics if the storage is bounded and full.
+    pub fn push_by_with_evicted<F, E>(&mut self, item: T, is_less: F, evicted: E)
+    where
+        F: Fn(&T, &T) -> bool,
+        E: FnMut(T),
+    {
+        push_back(
+            &mut self.dq,
+            self.policy,
+            item,
+            |existing_item, item| is_less(existing_item, item),
+            |existing_item, item| is_less(item, existing_item),
+            evicted,
+        );
+    }
+
+    /// Pushes `item` to the front, as if it had been pushed before every
+    /// element the queue was fed, with the dominance rule of `push_by`
+    /// mirrored: `item` is older than every element, so it pops none.
+    ///
+    /// If `is_less(&item, front)` holds, `item` could never have been the
+    /// extremum and is handed back as `Err(item)`. Equal to the front, `item`
+    /// is stored under [`Policy::NonStrict`], handed back under
+    /// [`Policy::KeepEqualNewest`], and replaces the front under
+    /// [`Policy::KeepEqualOldest`].
+    ///
+    /// # Panics
+    /// Panics if the storage is bounded and full.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use monotonicqueue::MonotonicQueue;
+    ///
+    /// let mut mq = MonotonicQueue::max();
+    /// mq.extend([7, 4]);
+    ///
+    /// let is_less = |n1: &i32, n2: &i32| n1 < n2;
+    /// assert_eq!(mq.push_front_by(5, is_less), Err(5));
+    /// assert_eq!(mq.push_front_by(8, is_less), Ok(()));
+    /// assert!(mq.iter().eq(&[8, 7, 4]));
+    /// # }
+    /// ```
+    pub fn push_front_by<F>(&mut self, item: T, is_less: F) -> Result<(), T>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        storage::push_front(
+            &mut self.dq,
+            self.policy,
+            item,
+            |existing_item, item| is_less(existing_item, item),
+            |existing_item, item| is_less(item, existing_item),
+            drop,
+        )
+    }
+
+    /// Checks that the queue is monotonic under `is_less`, returning the
+    /// first adjacent pair found out of order.
+    ///
+    /// Pushes are checked as they happen when debug assertions are enabled,
+    /// and fully with the `checked` feature; this is the explicit check for
+    /// queues fed through `push_by` with closures.
+    pub fn validate_by<F>(&self, is_less: F) -> Result<(), InvariantViolation>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        invariant::validate(
+            &self.dq,
+            self.policy,
+            0..self.dq.len(),
+            |existing_item, item| is_less(existing_item, item),
+            |existing_item, item| is_less(item, existing_item),
+        )
+    }
+}
+
+impl<T, C, const N: usize> MonotonicQueue<T, C, ArrayDeque<T, N>> {
+    /// Create an empty monotonic queue over an empty [`ArrayDeque`], as
+    /// `with_storage` does, in a const context.
+    pub(crate) const fn with_array(cmp: C) -> MonotonicQueue<T, C, ArrayDeque<T, N>> {
+        MonotonicQueue {
+            dq: ArrayDeque::new(),
+            cmp,
+            policy: Policy::NonStrict,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T, C: Compare<T>, S: DequeStorage<T>> MonotonicQueue<T, C, S> {
+    /// Pushes `item` to the back, popping the elements it dominates under the
+    /// queue's comparator.
+    ///
+    /// # Panics
+    /// Panics if the storage is bounded and full.
+    pub fn push(&mut self, item: T) {
+        let cmp = &self.cmp;
+        push_back(
+            &mut self.dq,
+            self.policy,
+            item,
+            |existing_item, item| cmp.is_less(existing_item, item),
+            |existing_item, item| cmp.is_less(item, existing_item),
+            drop,
+        );
+    }
+
+    /// Pushes `item` like `push`, handing every element it evicts to
+    /// `evicted`, back to front. Under [`Policy::KeepEqualOldest`], a refused
+    /// `item` is handed over as well.
+    ///
+    /// # Panics
+    /// Panics if the storage is bounded and full.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use monotonicqueue::MonotonicQueue;
+    ///
+    /// let mut mq = MonotonicQueue::max();
+    /// mq.extend([9, 5, 3]);
+    ///
+    /// let mut superseded = Vec::new();
+    /// mq.push_with_evicted(6, |level| superseded.push(level));
+    ///
+    /// assert_eq!(superseded, vec![3, 5]);
+    /// # }
+    /// ```
+    pub fn push_with_evicted<E>(&mut self, item: T, evicted: E)
+    where
+        E: FnMut(T),
+    {
+        let cmp = &self.cmp;
+        push_back(
+            &mut self.dq,
+            self.policy,
+            item,
+            |existing_item, item| cmp.is_less(existing_item, item),
+            |existing_item, item| cmp.is_less(item, existing_item),
+            evicted,
+        );
+    }
+
+    /// Pushes `item` to the front under the queue's comparator, handing it
+    /// back if the front dominates it, as `push_front_by` does.
+    ///
+    /// # Panics
+    /// Panics if the storage is bounded and full.
+    pub fn push_front(&mut self, item: T) -> Result<(), T> {
+        let cmp = &self.cmp;
+        storage::push_front(
+            &mut self.dq,
+            self.policy,
+            item,
+            |existing_item, item| cmp.is_less(existing_item, item),
+            |existing_item, item| cmp.is_less(item, existing_item),
+            drop,
+        )
+    }
+
+    /// Checks that the queue is monotonic under its comparator, returning the
+    /// first adjacent pair found out of order.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use monotonicqueue::MonotonicQueue;
+    ///
+    /// let mut mq = MonotonicQueue::max();
+    /// mq.extend([9, 5, 7]);
+    ///
+    /// assert_eq!(mq.validate(), Ok(()));
+    /// assert!(mq.validate_by(|n1, n2| n1 > n2).is_err());
+    /// # }
+    /// ```
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        self.validate_by(|n1, n2| self.cmp.is_less(n1, n2))
+    }
+}
+
+impl<T: Copy, C: Compare<T>, S: DequeStorage<T>> MonotonicQueue<T, C, S> {
+    /// Pushes every element of `items` in order, leaving the queue as
+    /// pushing them one at a time would.
+    ///
+    /// Meant for slices of primitive numbers: `items` is processed in chunks,
+    /// each first compressed to the elements no later element of the chunk
+    /// dominates, its suffix extrema, so that only those are merged into the
+    /// queue.
+    ///
+    /// # Panics
+    /// Panics if the storage is bounded and full.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use monotonicqueue::MonotonicQueue;
+    ///
+    /// let mut mq = MonotonicQueue::max();
+    /// mq.extend_from_slice(&[3, 9, 4, 7, 1, 6]);
+    ///
+    /// assert!(mq.iter().eq(&[9, 7, 6]));
+    /// # }
+    /// ```
+    pub fn extend_from_slice(&mut self, items: &[T]) {
+        const CHUNK: usize = 64;
+
+        let cmp = &self.cmp;
+        let mut retained = [0; CHUNK];
+        for chunk in items.chunks(CHUNK) {
+            let mut len = 0;
+            for (index, item) in chunk.iter().enumerate().rev() {
+                if len == 0 {
+                    retained[len] = index;
+                    len += 1;
+                    continue;
+                }
+                let newer = &chunk[retained[len - 1]];
+                if cmp.is_less(newer, item) {
+                    retained[len] = index;
+                    len += 1;
+                } else if !cmp.is_less(item, newer) {
+                    match self.policy {
+                        Policy::NonStrict => {
+                            retained[len] = index;
+                            len += 1;
+                        }
+                        Policy::KeepEqualOldest => retained[len - 1] = index,
+                        Policy::KeepEqualNewest => {}
+                    }
+                }
+            }
+            for &index in retained[..len].iter().rev() {
+                push_back(
+                    &mut self.dq,
+                    self.policy,
+                    chunk[index],
+                    |existing_item, item| cmp.is_less(existing_item, item),
+                    |existing_item, item| cmp.is_less(item, existing_item),
+                    drop,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, F> MonotonicQueue<T, ByKey<F>> {
+    /// Create an empty monotonic queue whose front holds the element with the
+    /// greatest key.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::MonotonicQueue;
+    ///
+    /// let mut mq = MonotonicQueue::max_by_key(|(price, _qty): &(u32, u32)| *price);
+    /// mq.push((10, 5));
+    /// mq.push((12, 1));
+    ///
+    /// assert_eq!(mq.peek(), Some(&(12, 1)));
+    /// ```
+    pub fn max_by_key(key: F) -> MonotonicQueue<T, ByKey<F>> {
+        MonotonicQueue::with_comparator(ByKey::max(key))
+    }
+
+    /// Create an empty monotonic queue whose front holds the element with the
+    /// least key.
+    pub fn min_by_key(key: F) -> MonotonicQueue<T, ByKey<F>> {
+        MonotonicQueue::with_comparator(ByKey::min(key))
+    }
+}
+
+impl<T, C: Default, S: DequeStorage<T> + Default> Default for MonotonicQueue<T, C, S> {
+    fn default() -> MonotonicQueue<T, C, S> {
+        MonotonicQueue::with_storage(C::default(), S::default())
+    }
+}
+
+impl<T, C, S: fmt::Debug> fmt::Debug for MonotonicQueue<T, C, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MonotonicQueue")
+            .field("dq", &self.dq)
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Queues are equal when they retain the same elements under the same policy.
+/// Comparators are not compared, as closures cannot be.
+impl<T: PartialEq, C, S: DequeStorage<T>> PartialEq for MonotonicQueue<T, C, S> {
+    fn eq(&self, other: &MonotonicQueue<T, C, S>) -> bool {
+        self.policy == other.policy && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, C, S: DequeStorage<T>> Eq for MonotonicQueue<T, C, S> {}
+
+impl<T: Hash, C, S: DequeStorage<T>> Hash for MonotonicQueue<T, C, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.policy.hash(state);
+        state.write_usize(self.len());
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T, C: Compare<T>, S: DequeStorage<T>> Extend<T> for MonotonicQueue<T, C, S> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T, C, S> FromIterator<T> for MonotonicQueue<T, C, S>
+where
+    C: Compare<T> + Default,
+    S: DequeStorage<T> + Default,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> MonotonicQueue<T, C, S> {
+        let mut mq = MonotonicQueue::default();
+        mq.extend(iter);
+        mq
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, C, S: DequeStorage<T> + Default> MonotonicQueue<T, C, S> {
+    /// Create a queue holding `items` front to back as they are, without
+    /// pushing them, or None if they overflow the storage. The caller is
+    /// responsible for validating the result.
+    pub(crate) fn restore(
+        cmp: C,
+        policy: Policy,
+        items: Vec<T>,
+    ) -> Option<MonotonicQueue<T, C, S>> {
+        let mut mq = MonotonicQueue::with_storage(cmp, S::default()).with_policy(policy);
+        for item in items {
+            mq.dq.push_back(item).ok()?;
+        }
+        Some(mq)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T, C, S: DequeStorage<T>> MonotonicQueue<T, C, S> {
+    /// Returns the elements front to back, serializable as a sequence.
+    pub(crate) fn serialize_items(&self) -> SerializeItems<'_, T, C, S> {
+        SerializeItems(self)
+    }
+}
+
+/// The elements of a monotonic queue, serialized as a sequence.
+#[cfg(feature = "serde")]
+pub(crate) struct SerializeItems<'a, T, C, S>(&'a MonotonicQueue<T, C, S>);
+
+#[cfg(feature = "serde")]
+impl<T: Serialize, C, S: DequeStorage<T>> Serialize for SerializeItems<'_, T, C, S> {
+    fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        serializer.collect_seq(self.0)
+    }
+}
+
+/// Serializes the comparator, the policy and the elements front to back.
+#[cfg(feature = "serde")]
+impl<T: Serialize, C: Serialize, S: DequeStorage<T>> Serialize for MonotonicQueue<T, C, S> {
+    fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        let mut state = serializer.serialize_struct("MonotonicQueue", 3)?;
+        state.serialize_field("cmp", &self.cmp)?;
+        state.serialize_field("policy", &self.policy)?;
+        state.serialize_field("items", &self.serialize_items())?;
+        state.end()
+    }
+}
+
+/// Restores the elements as they were serialized, rejecting a snapshot that is
+/// not monotonic under its comparator and policy, or that overflows the
+/// storage.
+#[cfg(all(feature = "serde", feature = "std"))]
+impl<'de, T, C, S> Deserialize<'de> for MonotonicQueue<T, C, S>
+where
+    T: Deserialize<'de>,
+    C: Deserialize<'de> + Compare<T>,
+    S: DequeStorage<T> + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<MonotonicQueue<T, C, S>, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "MonotonicQueue")]
+        struct Snapshot<T, C> {
+            cmp: C,
+            policy: Policy,
+            items: Vec<T>,
+        }
+
+        let snapshot = Snapshot::deserialize(deserializer)?;
+        let mq = MonotonicQueue::restore(snapshot.cmp, snapshot.policy, snapshot.items)
+            .ok_or_else(|| D::Error::custom("snapshot overflows the queue storage"))?;
+        mq.validate().map_err(D::Error::custom)?;
+        Ok(mq)
+    }
+}
+
+impl<T, C, S: DequeStorage<T>> IntoIterator for MonotonicQueue<T, C, S> {
+    type Item = T;
+    type IntoIter = IntoIter<T, S>;
+
+    /// Consumes the queue into an iterator over its elements, front to back.
+    fn into_iter(self) -> IntoIter<T, S> {
+        IntoIter {
+            dq: self.dq,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, C, S: DequeStorage<T>> IntoIterator for &'a MonotonicQueue<T, C, S> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, S>;
+
+    fn into_iter(self) -> Iter<'a, T, S> {
+        self.iter()
+    }
+}
+
+/// An owning iterator over the elements of a [`MonotonicQueue`], front to
+/// back.
+#[derive(Clone, Debug)]
+pub struct IntoIter<T, #[cfg(feature = "std")] S = VecDeque<T>, #[cfg(not(feature = "std"))] S> {
+    dq: S,
+    marker: PhantomData<T>,
+}
+
+impl<T, S: DequeStorage<T>> Iterator for IntoIter<T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.dq.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.dq.len(), Some(self.dq.len()))
+    }
+}
+
+impl<T, S: DequeStorage<T>> DoubleEndedIterator for IntoIter<T, S> {
+    fn next_back(&mut self) -> Option<T> {
+        self.dq.pop_back()
+    }
+}
+
+impl<T, S: DequeStorage<T>> ExactSizeIterator for IntoIter<T, S> {}
+
+impl<T, S: DequeStorage<T>> FusedIterator for IntoIter<T, S> {}
+
+/// An iterator over references to the elements of a [`MonotonicQueue`], front
+/// to back.
+pub struct Iter<'a, T, #[cfg(feature = "std")] S = VecDeque<T>, #[cfg(not(feature = "std"))] S> {
+    dq: &'a S,
+    front: usize,
+    back: usize,
+    marker: PhantomData<&'a T>,
+}
+
+impl<T, S> Clone for Iter<'_, T, S> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+impl<T: fmt::Debug, S: DequeStorage<T>> fmt::Debug for Iter<'_, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Iter").field(&Entries(self.clone())).finish()
+    }
+}
+
+/// The elements of an iterator, debug formatted as a list.
+struct Entries<I>(I);
+
+impl<I: Iterator + Clone> fmt::Debug for Entries<I>
+where
+    I::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.0.clone()).finish()
+    }
+}
+
+impl<'a, T, S: DequeStorage<T>> Iterator for Iter<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        self.dq.get(self.front - 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.back - self.front, Some(self.back - self.front))
+    }
+}
+
+impl<T, S: DequeStorage<T>> DoubleEndedIterator for Iter<'_, T, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        self.dq.get(self.back)
+    }
+}
+
+impl<T, S: DequeStorage<T>> ExactSizeIterator for Iter<'_, T, S> {}
+
+impl<T, S: DequeStorage<T>> FusedIterator for Iter<'_, T, S> {}
+
+/// Pushes `item` to the back of `dq` under `policy`, see
+/// [`storage::push_back`].
+///
+/// # Panics
+/// Panics if `dq` is bounded and full.
+fn push_back<T, S, F, G, E>(
+    dq: &mut S,
+    policy: Policy,
+    item: T,
+    is_below: F,
+    is_above: G,
+    evicted: E,
+) where
+    S: DequeStorage<T>,
+    F: Fn(&T, &T) -> bool,
+    G: Fn(&T, &T) -> bool,
+    E: FnMut(T),
+{
+    if storage::push_back(dq, policy, item, is_below, is_above, evicted).is_err() {
+        panic!("monotonic queue storage is full");
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::cell::Cell;
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::VecDeque;
+    use std::hash::{Hash, Hasher};
+
+    use crate::testing::{xorshift, POLICIES};
+    use crate::{ArrayDeque, ByKey, Direction, MonotonicQueue, Policy};
+
+    #[test]
+    fn monotonic_incresing_queue() {
+        let mut mq = MonotonicQueue::new();
+
+        let is_less = |n1: &i32, n2: &i32| n1.gt(n2);
+        mq.push_by(1, is_less);
+        mq.push_by(2, is_less);
+
+        assert_eq!(mq.peek(), Some(&1));
+    }
+
+    #[test]
+    fn monotonic_decreasing_queue() {
+        let mut mq = MonotonicQueue::new();
+
+        let is_less = |n1: &i32, n2: &i32| n1.lt(n2);
+        mq.push_by(1, is_less);
+        mq.push_by(2, is_less);
+
+        assert_eq!(mq.peek(), Some(&2));
+    }
+
+    #[test]
+    fn monotonic_max_queue() {
+        let mut mq = MonotonicQueue::max();
+
+        for n in [3, 1, 2, 2] {
+            mq.push(n);
+        }
+
+        assert_eq!(mq.pop(), Some(3));
+        assert_eq!(mq.pop(), Some(2));
+    }
+
+    #[test]
+    fn monotonic_min_queue() {
+        let mut mq = MonotonicQueue::min();
+
+        for n in [3, 1, 2, 4] {
+            mq.push(n);
+        }
+
+        assert_eq!(mq.pop(), Some(1));
+        assert_eq!(mq.pop(), Some(2));
+        assert_eq!(mq.pop(), Some(4));
+        assert_eq!(mq.pop(), None);
+    }
+
+    #[test]
+    fn monotonic_queue_policies() {
+        let drain = |policy: Policy| {
+            let mut mq = MonotonicQueue::max().with_policy(policy);
+            for (key, tag) in [(3, 'a'), (2, 'b'), (2, 'c'), (1, 'd')] {
+                mq.push_by((key, tag), |n1, n2| n1.0 < n2.0);
+            }
+            std::iter::from_fn(|| mq.pop()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            drain(Policy::NonStrict),
+            vec![(3, 'a'), (2, 'b'), (2, 'c'), (1, 'd')]
+        );
+        assert_eq!(
+            drain(Policy::KeepEqualOldest),
+            vec![(3, 'a'), (2, 'b'), (1, 'd')]
+        );
+        assert_eq!(
+            drain(Policy::KeepEqualNewest),
+            vec![(3, 'a'), (2, 'c'), (1, 'd')]
+        );
+    }
+
+    #[test]
+    fn monotonic_queue_with_comparator() {
+        let mut mq = MonotonicQueue::with_comparator(|n1: &i32, n2: &i32| n1.abs() < n2.abs());
+
+        for n in [-5, 3, -4, 1] {
+            mq.push(n);
+        }
+
+        assert_eq!(mq.pop(), Some(-5));
+        assert_eq!(mq.pop(), Some(-4));
+        assert_eq!(mq.pop(), Some(1));
+    }
+
+    #[test]
+    fn monotonic_queue_by_key() {
+        let mut mq = MonotonicQueue::max_by_key(|(price, _): &(i32, char)| *price);
+        mq.push((4, 'a'));
+        mq.push((2, 'b'));
+        mq.push((3, 'c'));
+        assert_eq!(mq.peek(), Some(&(4, 'a')));
+
+        let mut mq = MonotonicQueue::max().with_policy(Policy::KeepEqualNewest);
+        for item in [(4, 'a'), (2, 'b'), (4, 'c')] {
+            mq.push_by_key(item, |(price, _)| *price);
+        }
+        assert_eq!(mq.pop(), Some((4, 'c')));
+        assert_eq!(mq.pop(), None);
+    }
+
+    #[test]
+    fn monotonic_queue_default_debug_clone() {
+        let mut mq: MonotonicQueue<i32> = MonotonicQueue::default();
+        assert_eq!(mq.direction(), Direction::Max);
+
+        mq.push(2);
+        mq.push(1);
+        let cloned = mq.clone();
+        mq.pop();
+
+        assert_eq!(cloned.peek(), Some(&2));
+        assert_eq!(
+            format!("{:?}", cloned),
+            "MonotonicQueue { dq: [2, 1], policy: NonStrict, .. }"
+        );
+    }
+
+    #[test]
+    fn monotonic_queue_eq_hash() {
+        let hash = |mq: &MonotonicQueue<i32>| {
+            let mut hasher = DefaultHasher::new();
+            mq.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let mq1: MonotonicQueue<i32> = [5, 1, 3].into_iter().collect();
+        let mut mq2 = MonotonicQueue::max();
+        mq2.extend([4, 5, 3]);
+
+        assert_eq!(mq1, mq2);
+        assert_eq!(hash(&mq1), hash(&mq2));
+        assert_ne!(mq1, mq2.clone().with_policy(Policy::KeepEqualNewest));
+
+        mq2.push(2);
+        assert_ne!(mq1, mq2);
+    }
+
+    #[test]
+    fn monotonic_queue_into_iter() {
+        let mut mq = MonotonicQueue::min();
+        mq.extend([3, 1, 2, 4]);
+
+        let by_ref: Vec<_> = (&mq).into_iter().copied().collect();
+        let by_value: Vec<_> = mq.into_iter().rev().collect();
+
+        assert_eq!(by_ref, vec![1, 2, 4]);
+        assert_eq!(by_value, vec![4, 2, 1]);
+    }
+
+    #[test]
+    fn monotonic_queue_inspection() {
+        let mut mq = MonotonicQueue::max();
+        assert!(mq.is_empty());
+        assert_eq!(mq.peek_back(), None);
+
+        mq.extend([9, 4, 7, 3, 1]);
+        mq.pop();
+        mq.push(2);
+
+        assert_eq!(mq.len(), 3);
+        assert_eq!(mq.peek_back(), Some(&2));
+        assert_eq!(mq.get(0), Some(&7));
+        assert!(mq.iter().eq(&[7, 3, 2]));
+
+        let (front, back) = mq.as_slices();
+        assert_eq!([front, back].concat(), vec![7, 3, 2]);
+    }
+
+    #[test]
+    fn monotonic_queue_with_array_storage() {
+        let mut mq = MonotonicQueue::with_storage(Direction::Max, ArrayDeque::<i32, 3>::new());
+        mq.extend([6, 2, 5, 4]);
+
+        assert_eq!(mq.len(), 3);
+        assert!(mq.iter().rev().eq(&[4, 5, 6]));
+        assert_eq!(mq.clone().into_iter().collect::<Vec<_>>(), vec![6, 5, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "storage is full")]
+    fn monotonic_queue_full_storage_panics() {
+        let mut mq = MonotonicQueue::with_storage(Direction::Min, ArrayDeque::<i32, 2>::new());
+        mq.extend([1, 2, 3]);
+    }
+
+    #[test]
+    fn monotonic_queue_push_with_evicted() {
+        let mut mq = MonotonicQueue::min().with_policy(Policy::KeepEqualOldest);
+        mq.extend([1, 4, 6, 8]);
+
+        let mut evicted = Vec::new();
+        mq.push_with_evicted(5, |n| evicted.push(n));
+        mq.push_with_evicted(5, |n| evicted.push(n));
+        mq.push_by_with_evicted(0, |n1, n2| n1 > n2, |n| evicted.push(n));
+
+        assert_eq!(evicted, vec![8, 6, 5, 5, 4, 1]);
+        assert!(mq.iter().eq(&[0]));
+    }
+
+    #[test]
+    fn monotonic_queue_extend_from_slice() {
+        let mut seed = 0x9e37_79b9_u32;
+        let items: Vec<(u32, usize)> = (0..1000)
+            .map(|index| (xorshift(&mut seed) % 50, index))
+            .collect();
+
+        for direction in [Direction::Max, Direction::Min] {
+            for policy in POLICIES {
+                for len in [0, 1, 63, 64, 65, 1000] {
+                    let new = || {
+                        let mut mq = MonotonicQueue::with_comparator(ByKey::new(
+                            direction,
+                            |(value, _): &(u32, usize)| *value,
+                        ))
+                        .with_policy(policy);
+                        mq.push((25, usize::MAX));
+                        mq
+                    };
+                    let mut one_by_one = new();
+                    let mut batched = new();
+
+                    one_by_one.extend(items[..len].iter().copied());
+                    batched.extend_from_slice(&items[..len]);
+
+                    assert_eq!(one_by_one, batched);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn monotonic_queue_validate() {
+        let descending = Cell::new(true);
+        let mut mq =
+            MonotonicQueue::with_comparator(
+                |n1: &i32, n2: &i32| {
+                    if descending.get() {
+                        n1 < n2
+                    } else {
+                        n1 > n2
+                    }
+                },
+            )
+            .with_policy(Policy::KeepEqualOldest);
+        mq.extend([9, 5, 4, 3, 3]);
+        assert_eq!(mq.validate(), Ok(()));
+
+        descending.set(false);
+        let violation = mq.validate().unwrap_err();
+        assert_eq!(violation.index(), 0);
+        assert_eq!(
+            violation.to_string(),
+            "elements at 0 and 1 are out of monotonic order"
+        );
+        assert_eq!(mq.validate_by(|n1, n2| n1 < n2), Ok(()));
+    }
+
+    #[test]
+    #[cfg_attr(debug_assertions, should_panic(expected = "invariant violated"))]
+    fn monotonic_queue_push_by_checks_invariant() {
+        let mut mq = MonotonicQueue::max();
+        mq.extend([9, 5]);
+        mq.push_by(7, |n1, n2| n1 > n2);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn monotonic_queue_serde() {
+        let mut mq = MonotonicQueue::min().with_policy(Policy::KeepEqualNewest);
+        mq.extend([4, 1, 3, 3, 5]);
+
+        let json = serde_json::to_string(&mq).unwrap();
+        assert_eq!(
+            json,
+            r#"{"cmp":"Min","policy":"KeepEqualNewest","items":[1,3,5]}"#
+        );
+        let restored: MonotonicQueue<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, mq);
+        assert_eq!(restored.direction(), Direction::Min);
+
+        let corrupted = r#"{"cmp":"Min","policy":"KeepEqualNewest","items":[1,5,3]}"#;
+        let error = serde_json::from_str::<MonotonicQueue<i32>>(corrupted).unwrap_err();
+        assert!(error.to_string().contains("out of monotonic order"));
+
+        let duplicated = r#"{"cmp":"Min","policy":"KeepEqualNewest","items":[1,3,3]}"#;
+        assert!(serde_json::from_str::<MonotonicQueue<i32>>(duplicated).is_err());
+
+        let overflowing = r#"{"cmp":"Min","policy":"NonStrict","items":[1,2,3]}"#;
+        assert!(
+            serde_json::from_str::<MonotonicQueue<i32, Direction, ArrayDeque<i32, 2>>>(overflowing)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn monotonic_queue_push_front() {
+        // Every sequence of up to 5 pushes of 0, 1 or 2 at either end, against
+        // a queue fed the logical sequence at the back. Items are tagged so
+        // that ties are told apart.
+        for direction in [Direction::Max, Direction::Min] {
+            for policy in POLICIES {
+                let new = || {
+                    MonotonicQueue::with_comparator(ByKey::new(
+                        direction,
+                        |(value, _): &(u8, usize)| *value,
+                    ))
+                    .with_policy(policy)
+                };
+                for len in 0..=5 {
+                    for mut code in 0..6usize.pow(len) {
+                        let mut mq = new();
+                        let mut logical = VecDeque::new();
+                        for tag in 0..len as usize {
+                            let item = ((code % 3) as u8, tag);
+                            let stored = if code / 3 % 2 == 0 {
+                                mq.push(item);
+                                logical.push_back(item);
+                                mq.iter().any(|other| *other == item)
+                            } else {
+                                logical.push_front(item);
+                                mq.push_front(item).is_ok()
+                            };
+                            code /= 6;
+
+                            let mut model = new();
+                            model.extend(logical.iter().copied());
+                            assert_eq!(mq, model);
+                            assert_eq!(stored, model.iter().any(|other| *other == item));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn monotonic_queue_pop_back() {
+        let mut mq = MonotonicQueue::min();
+        mq.extend([1, 4, 3]);
+
+        assert_eq!(mq.pop_back(), Some(3));
+        assert_eq!(mq.push_front(2), Err(2));
+        assert_eq!(mq.push_front(0), Ok(()));
+        assert!(mq.iter().eq(&[0, 1]));
+        assert_eq!(mq.pop_back(), Some(1));
+        assert_eq!(mq.pop_back(), Some(0));
+        assert_eq!(mq.pop_back(), None);
+    }
+}
@@ -0,0 +1,256 @@
+use std::cell::UnsafeCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::hint;
+use std::mem::MaybeUninit;
+use std::ptr;
+// The std primitives rather than `crate::sync`'s: the optimistic read of a
+// sequence lock is a race loom would report.
+use std::sync::atomic::{self, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::{Compare, DequeStorage, Direction, MonotonicQueue};
+
+/// Sequence lock holding a copy of the front of the queue. The sequence is odd
+/// while the writer updates the value.
+struct SeqLock<T> {
+    seq: AtomicUsize,
+    value: UnsafeCell<Option<T>>,
+}
+
+// Safety: readers only keep copies read while the sequence did not change.
+unsafe impl<T: Copy + Send> Sync for SeqLock<T> {}
+
+impl<T: Copy> SeqLock<T> {
+    /// Stores `value`. Only one thread may write at a time.
+    fn write(&self, value: Option<T>) {
+        let seq = self.seq.load(Ordering::Relaxed);
+        self.seq.store(seq.wrapping_add(1), Ordering::Relaxed);
+        atomic::fence(Ordering::Release);
+        // Safety: readers discard what they read during the write.
+        unsafe { ptr::write_volatile(self.value.get(), value) };
+        self.seq.store(seq.wrapping_add(2), Ordering::Release);
+    }
+
+    /// Returns a copy of the value, retrying while it is being written.
+    fn read(&self) -> Option<T> {
+        loop {
+            let seq = self.seq.load(Ordering::Acquire);
+            if seq % 2 == 1 {
+                hint::spin_loop();
+                continue;
+            }
+            // Safety: the copy may be torn, and is only used if the sequence
+            // shows no write overlapped it.
+            let value =
+                unsafe { ptr::read_volatile(self.value.get().cast::<MaybeUninit<Option<T>>>()) };
+            atomic::fence(Ordering::Acquire);
+            if self.seq.load(Ordering::Relaxed) == seq {
+                // Safety: no write overlapped the copy.
+                return unsafe { value.assume_init() };
+            }
+        }
+    }
+}
+
+/// Shared Monotonic Queue is a [`MonotonicQueue`] whose front is published to
+/// any number of [`SharedReader`]s on other threads.
+///
+/// The queue has a single writer, the owner of this value; the front is
+/// republished after every push or pop through a sequence lock, so readers
+/// never block the writer. Readers retry, spinning, while a publication is in
+/// progress.
+///
+/// # Example
+/// ```
+/// use std::thread;
+///
+/// use monotonicqueue::{MonotonicQueue, SharedMonotonicQueue};
+///
+/// let mut sq = SharedMonotonicQueue::new(MonotonicQueue::max());
+/// let reader = sq.reader();
+///
+/// sq.push(3);
+/// sq.push(7);
+/// sq.push(5);
+///
+/// let max = thread::spawn(move || reader.peek()).join().unwrap();
+/// assert_eq!(max, Some(7));
+/// ```
+pub struct SharedMonotonicQueue<T, C = Direction, S = VecDeque<T>> {
+    mq: MonotonicQueue<T, C, S>,
+    front: Arc<SeqLock<T>>,
+}
+
+impl<T: Copy, C, S: DequeStorage<T>> SharedMonotonicQueue<T, C, S> {
+    /// Create a shared queue over `mq`, publishing its front.
+    pub fn new(mq: MonotonicQueue<T, C, S>) -> SharedMonotonicQueue<T, C, S> {
+        let front = Arc::new(SeqLock {
+            seq: AtomicUsize::new(0),
+            value: UnsafeCell::new(mq.peek().copied()),
+        });
+        SharedMonotonicQueue { mq, front }
+    }
+
+    /// Returns the underlying queue, for inspection.
+    pub fn queue(&self) -> &MonotonicQueue<T, C, S> {
+        &self.mq
+    }
+
+    /// Returns the underlying queue. Readers keep the front last published.
+    pub fn into_inner(self) -> MonotonicQueue<T, C, S> {
+        self.mq
+    }
+
+    /// Returns a new reader of the front of the queue.
+    pub fn reader(&self) -> SharedReader<T> {
+        SharedReader {
+            front: self.front.clone(),
+        }
+    }
+
+    /// Removes the front element and returns it, or None if empty, then
+    /// publishes the new front.
+    pub fn pop(&mut self) -> Option<T> {
+        let item = self.mq.pop()?;
+        self.publish();
+        Some(item)
+    }
+
+    /// Pushes `item` as [`MonotonicQueue::push_by`] does, then publishes the
+    /// new front.
+    pub fn push_by<F>(&mut self, item: T, is_less: F)
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        self.mq.push_by(item, is_less);
+        self.publish();
+    }
+
+    fn publish(&self) {
+        self.front.write(self.mq.peek().copied());
+    }
+}
+
+impl<T: Copy, C: Compare<T>, S: DequeStorage<T>> SharedMonotonicQueue<T, C, S> {
+    /// Pushes `item` under the queue's comparator, then publishes the new
+    /// front.
+    pub fn push(&mut self, item: T) {
+        self.mq.push(item);
+        self.publish();
+    }
+}
+
+impl<T: Copy, C, S: DequeStorage<T>> From<MonotonicQueue<T, C, S>>
+    for SharedMonotonicQueue<T, C, S>
+{
+    fn from(mq: MonotonicQueue<T, C, S>) -> SharedMonotonicQueue<T, C, S> {
+        SharedMonotonicQueue::new(mq)
+    }
+}
+
+impl<T, C, S: fmt::Debug> fmt::Debug for SharedMonotonicQueue<T, C, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedMonotonicQueue")
+            .field("mq", &self.mq)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A reader of the front of a [`SharedMonotonicQueue`], which may be cloned
+/// and sent to other threads.
+pub struct SharedReader<T> {
+    front: Arc<SeqLock<T>>,
+}
+
+impl<T: Copy> SharedReader<T> {
+    /// Returns a copy of the front as of the writer's latest push or pop, or
+    /// None if the queue was empty.
+    pub fn peek(&self) -> Option<T> {
+        self.front.read()
+    }
+}
+
+impl<T> Clone for SharedReader<T> {
+    fn clone(&self) -> SharedReader<T> {
+        SharedReader {
+            front: self.front.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for SharedReader<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedReader").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    use crate::testing::xorshift;
+    use crate::{MonotonicQueue, SharedMonotonicQueue};
+
+    #[test]
+    fn shared_queue_readers_never_see_torn_fronts() {
+        const PUSHES: u64 = 200_000;
+        // Every word of a published front holds the same value, so a torn
+        // read shows different words.
+        let mut sq = SharedMonotonicQueue::new(MonotonicQueue::max());
+        sq.push([0_u64; 8]);
+        let done = Arc::new(AtomicBool::new(false));
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let reader = sq.reader();
+                let done = done.clone();
+                thread::spawn(move || {
+                    let mut reads = 0_u64;
+                    while !done.load(Ordering::Relaxed) {
+                        // The queue is never empty, and fronts are pushed
+                        // values.
+                        let front = reader.peek().expect("front was published");
+                        assert!(front.iter().all(|&word| word == front[0]));
+                        assert!(front[0] < PUSHES);
+                        reads += 1;
+                    }
+                    reads
+                })
+            })
+            .collect();
+
+        let mut seed = 0x2545_f491_u32;
+        for _ in 1..PUSHES {
+            let seed = xorshift(&mut seed);
+            if seed & 3 == 0 && sq.queue().len() > 1 {
+                sq.pop();
+            } else {
+                sq.push([u64::from(seed) % PUSHES; 8]);
+            }
+        }
+        done.store(true, Ordering::Relaxed);
+
+        for reader in readers {
+            assert!(reader.join().unwrap() > 0);
+        }
+        assert_eq!(sq.reader().peek(), sq.queue().peek().copied());
+    }
+
+    #[test]
+    fn shared_queue_publishes_empty_front() {
+        let mut sq = SharedMonotonicQueue::new(MonotonicQueue::min());
+        let reader = sq.reader();
+        assert_eq!(reader.peek(), None);
+
+        sq.push_by(4, |n1: &i32, n2: &i32| n1 > n2);
+        sq.push_by(2, |n1, n2| n1 > n2);
+        assert_eq!(reader.clone().peek(), Some(2));
+
+        sq.pop();
+        assert_eq!(reader.peek(), None);
+        assert!(sq.into_inner().is_empty());
+    }
+}
@@ -0,0 +1,604 @@
+use std::fmt;
+
+use crate::{
+    Compare, DequeStorage, Direction, Float, FloatOrder, InvariantViolation, MonotonicQueue,
+    NanPolicy, Policy, SlidingWindow,
+};
+
+/// Leading bytes of every snapshot.
+const MAGIC: [u8; 4] = *b"MQSN";
+
+/// Version of the snapshot layout written by `to_bytes`.
+const VERSION: u8 = 1;
+
+/// Kind byte of a [`MonotonicQueue`] snapshot.
+const KIND_QUEUE: u8 = 0;
+
+/// Kind byte of a [`SlidingWindow`] snapshot.
+const KIND_WINDOW: u8 = 1;
+
+/// Kind byte of a [`MonotonicQueue`] snapshot ordered by a [`FloatOrder`].
+const KIND_FLOAT_QUEUE: u8 = 2;
+
+/// Length of the header: magic, version, kind, direction, policy, value size,
+/// then window length, next position and element count as `u64`. The bits of
+/// the direction byte above the direction hold the NaN policy of float queues.
+const HEADER_LEN: usize = 4 + 5 + 3 * 8;
+
+/// Fixed-size values a snapshot can hold, encoded in little-endian.
+pub trait Plain: Copy {
+    /// Number of bytes of an encoded value, from 1 to 255.
+    const SIZE: usize;
+
+    /// Appends the encoding of `self` to `out`.
+    fn encode(self, out: &mut Vec<u8>);
+
+    /// Decodes a value from exactly `SIZE` bytes, or returns None.
+    fn decode(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! impl_plain {
+    ($($t:ty),*) => {
+        $(
+            impl Plain for $t {
+                const SIZE: usize = std::mem::size_of::<$t>();
+
+                fn encode(self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(bytes: &[u8]) -> Option<$t> {
+                    bytes.try_into().ok().map(<$t>::from_le_bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_plain!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+/// The error returned when decoding a malformed snapshot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SnapshotError {
+    /// The input ends before the snapshot does.
+    Truncated,
+    /// The input does not start with the snapshot magic bytes.
+    BadMagic,
+    /// The snapshot was written in a layout version this crate cannot read.
+    UnsupportedVersion(u8),
+    /// The header or the elements are inconsistent; the message tells why.
+    Malformed(&'static str),
+    /// The elements are not monotonic under the snapshot's direction and
+    /// policy.
+    OutOfOrder(InvariantViolation),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Truncated => f.write_str("snapshot is truncated"),
+            SnapshotError::BadMagic => f.write_str("input is not a snapshot"),
+            SnapshotError::UnsupportedVersion(version) => {
+                write!(f, "unsupported snapshot version {}", version)
+            }
+            SnapshotError::Malformed(reason) => write!(f, "malformed snapshot: {}", reason),
+            SnapshotError::OutOfOrder(violation) => write!(f, "malformed snapshot: {}", violation),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// Header fields shared by every snapshot kind.
+struct Header {
+    direction: Direction,
+    nan: NanPolicy,
+    policy: Policy,
+    window: u64,
+    next: u64,
+    count: usize,
+}
+
+impl Header {
+    fn encode(&self, kind: u8, size: usize, out: &mut Vec<u8>) {
+        out.extend_from_slice(&MAGIC);
+        out.push(VERSION);
+        out.push(kind);
+        let direction = match self.direction {
+            Direction::Max => 0,
+            Direction::Min => 1,
+        };
+        let nan = match self.nan {
+            NanPolicy::TotalOrder => 0,
+            NanPolicy::Greatest => 1,
+            NanPolicy::Least => 2,
+            NanPolicy::Reject => 3,
+            NanPolicy::Skip => 4,
+        };
+        out.push(direction | nan << 1);
+        out.push(match self.policy {
+            Policy::NonStrict => 0,
+            Policy::KeepEqualOldest => 1,
+            Policy::KeepEqualNewest => 2,
+        });
+        out.push(size as u8);
+        self.window.encode(out);
+        self.next.encode(out);
+        (self.count as u64).encode(out);
+    }
+
+    /// Decodes the header of a `kind` snapshot of `entry` bytes long elements
+    /// holding `size` bytes long values, checking that exactly `count`
+    /// elements follow it.
+    fn decode(bytes: &[u8], kind: u8, size: usize, entry: usize) -> Result<Header, SnapshotError> {
+        if bytes.len() < MAGIC.len() {
+            return Err(SnapshotError::Truncated);
+        }
+        if bytes[..MAGIC.len()] != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        let header = bytes.get(..HEADER_LEN).ok_or(SnapshotError::Truncated)?;
+        if header[4] != VERSION {
+            return Err(SnapshotError::UnsupportedVersion(header[4]));
+        }
+        if header[5] != kind {
+            return Err(SnapshotError::Malformed("snapshot kind mismatch"));
+        }
+        let direction = match header[6] & 1 {
+            0 => Direction::Max,
+            _ => Direction::Min,
+        };
+        let nan = match header[6] >> 1 {
+            0 => NanPolicy::TotalOrder,
+            1 => NanPolicy::Greatest,
+            2 => NanPolicy::Least,
+            3 => NanPolicy::Reject,
+            4 => NanPolicy::Skip,
+            _ => return Err(SnapshotError::Malformed("unknown NaN policy")),
+        };
+        if kind != KIND_FLOAT_QUEUE && nan != NanPolicy::TotalOrder {
+            return Err(SnapshotError::Malformed("snapshot holds a NaN policy"));
+        }
+        let policy = match header[7] {
+            0 => Policy::NonStrict,
+            1 => Policy::KeepEqualOldest,
+            2 => Policy::KeepEqualNewest,
+            _ => return Err(SnapshotError::Malformed("unknown policy")),
+        };
+        if size == 0 || usize::from(header[8]) != size {
+            return Err(SnapshotError::Malformed("value size mismatch"));
+        }
+        let field = |index: usize| u64::decode(&header[9 + 8 * index..17 + 8 * index]);
+        let (window, next, count) = match (field(0), field(1), field(2)) {
+            (Some(window), Some(next), Some(count)) => (window, next, count),
+            _ => return Err(SnapshotError::Truncated),
+        };
+        let body = bytes.len() - HEADER_LEN;
+        let count = usize::try_from(count).map_err(|_| SnapshotError::Truncated)?;
+        let expected = count.checked_mul(entry).ok_or(SnapshotError::Truncated)?;
+        if body < expected {
+            return Err(SnapshotError::Truncated);
+        }
+        if body > expected {
+            return Err(SnapshotError::Malformed("trailing bytes"));
+        }
+        Ok(Header {
+            direction,
+            nan,
+            policy,
+            window,
+            next,
+            count,
+        })
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for crate::Direction {}
+    impl Sealed for crate::FloatOrder {}
+}
+
+/// Comparators a queue snapshot can record: [`Direction`], and [`FloatOrder`]
+/// along with its NaN policy.
+pub trait SnapshotOrder<T>: Compare<T> + sealed::Sealed + Sized {
+    #[doc(hidden)]
+    const KIND: u8;
+
+    #[doc(hidden)]
+    fn parts(&self) -> (Direction, NanPolicy);
+
+    #[doc(hidden)]
+    fn from_parts(direction: Direction, nan: NanPolicy) -> Self;
+
+    /// Returns false if a queue ordered by `self` never holds `item`.
+    #[doc(hidden)]
+    fn admits(&self, _item: &T) -> bool {
+        true
+    }
+}
+
+impl<T: Ord> SnapshotOrder<T> for Direction {
+    const KIND: u8 = KIND_QUEUE;
+
+    fn parts(&self) -> (Direction, NanPolicy) {
+        (*self, NanPolicy::TotalOrder)
+    }
+
+    fn from_parts(direction: Direction, _nan: NanPolicy) -> Direction {
+        direction
+    }
+}
+
+impl<T: Float> SnapshotOrder<T> for FloatOrder {
+    const KIND: u8 = KIND_FLOAT_QUEUE;
+
+    fn parts(&self) -> (Direction, NanPolicy) {
+        (self.direction(), self.nan_policy())
+    }
+
+    fn from_parts(direction: Direction, nan: NanPolicy) -> FloatOrder {
+        FloatOrder::new(direction, nan)
+    }
+
+    fn admits(&self, item: &T) -> bool {
+        !(item.is_nan() && matches!(self.nan_policy(), NanPolicy::Reject | NanPolicy::Skip))
+    }
+}
+
+impl<T: Plain, C: SnapshotOrder<T>, S: DequeStorage<T>> MonotonicQueue<T, C, S> {
+    /// Encodes the queue into a versioned binary snapshot, holding its
+    /// direction, policy and elements, and the NaN policy of a float queue.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::{FloatOrder, MonotonicQueue, NanPolicy};
+    ///
+    /// let mut mq = MonotonicQueue::min();
+    /// mq.extend([3u32, 1, 2]);
+    ///
+    /// let bytes = mq.to_bytes();
+    /// let restored: MonotonicQueue<u32> = MonotonicQueue::from_bytes(&bytes).unwrap();
+    /// assert_eq!(restored, mq);
+    ///
+    /// let mut mq = MonotonicQueue::max_float(NanPolicy::Greatest);
+    /// mq.extend([1.5, f64::NAN, 0.5]);
+    ///
+    /// let bytes = mq.to_bytes();
+    /// let restored: MonotonicQueue<f64, FloatOrder> = MonotonicQueue::from_bytes(&bytes).unwrap();
+    /// assert_eq!(restored.comparator(), mq.comparator());
+    /// assert!(restored.peek().unwrap().is_nan());
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let (direction, nan) = self.comparator().parts();
+        let mut out = Vec::with_capacity(HEADER_LEN + self.len() * T::SIZE);
+        let header = Header {
+            direction,
+            nan,
+            policy: self.policy(),
+            window: 0,
+            next: 0,
+            count: self.len(),
+        };
+        header.encode(C::KIND, T::SIZE, &mut out);
+        for &item in self {
+            item.encode(&mut out);
+        }
+        out
+    }
+}
+
+impl<T: Plain, C: SnapshotOrder<T>, S: DequeStorage<T> + Default> MonotonicQueue<T, C, S> {
+    /// Decodes a queue from a snapshot written by `to_bytes`, rejecting
+    /// malformed input, including elements out of monotonic order and NaN
+    /// under a NaN policy refusing it.
+    pub fn from_bytes(bytes: &[u8]) -> Result<MonotonicQueue<T, C, S>, SnapshotError> {
+        let header = Header::decode(bytes, C::KIND, T::SIZE, T::SIZE)?;
+        if header.window != 0 || header.next != 0 {
+            return Err(SnapshotError::Malformed(
+                "queue snapshot holds window fields",
+            ));
+        }
+        let items = bytes[HEADER_LEN..]
+            .chunks_exact(T::SIZE)
+            .take(header.count)
+            .map(T::decode)
+            .collect::<Option<Vec<T>>>()
+            .ok_or(SnapshotError::Truncated)?;
+        let cmp = C::from_parts(header.direction, header.nan);
+        if !items.iter().all(|item| cmp.admits(item)) {
+            return Err(SnapshotError::Malformed("snapshot holds a refused NaN"));
+        }
+        let mq = MonotonicQueue::restore(cmp, header.policy, items).ok_or(
+            SnapshotError::Malformed("snapshot overflows the queue storage"),
+        )?;
+        mq.validate().map_err(SnapshotError::OutOfOrder)?;
+        Ok(mq)
+    }
+}
+
+impl<T: Plain + Ord> SlidingWindow<T> {
+    /// Encodes the window into a versioned binary snapshot, holding its
+    /// direction, window length, next position and retained elements with
+    /// their positions. Only windows ordered by a [`Direction`] have one.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::SlidingWindow;
+    ///
+    /// let mut sw = SlidingWindow::max(3);
+    /// for n in [5i64, 1, 4, 2] {
+    ///     sw.push(n);
+    /// }
+    ///
+    /// let mut restored: SlidingWindow<i64> = SlidingWindow::from_bytes(&sw.to_bytes()).unwrap();
+    /// restored.push(0);
+    ///
+    /// assert_eq!(restored.peek(), Some(&4));
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let entry = u64::SIZE + T::SIZE;
+        let count = self.positioned().len();
+        let mut out = Vec::with_capacity(HEADER_LEN + count * entry);
+        let header = Header {
+            direction: *self.comparator(),
+            nan: NanPolicy::TotalOrder,
+            policy: Policy::NonStrict,
+            window: self.window() as u64,
+            next: self.next_position() as u64,
+            count,
+        };
+        header.encode(KIND_WINDOW, T::SIZE, &mut out);
+        for &(position, item) in self.positioned() {
+            (position as u64).encode(&mut out);
+            item.encode(&mut out);
+        }
+        out
+    }
+
+    /// Decodes a window from a snapshot written by `to_bytes`, rejecting
+    /// malformed input, including inconsistent positions and elements out of
+    /// monotonic order.
+    pub fn from_bytes(bytes: &[u8]) -> Result<SlidingWindow<T>, SnapshotError> {
+        let entry = u64::SIZE + T::SIZE;
+        let header = Header::decode(bytes, KIND_WINDOW, T::SIZE, entry)?;
+        if header.policy != Policy::NonStrict {
+            return Err(SnapshotError::Malformed("window snapshot holds a policy"));
+        }
+        let (window, next) = match (usize::try_from(header.window), usize::try_from(header.next)) {
+            (Ok(window), Ok(next)) => (window, next),
+            _ => return Err(SnapshotError::Malformed("window does not fit in memory")),
+        };
+        let items = bytes[HEADER_LEN..]
+            .chunks_exact(entry)
+            .take(header.count)
+            .map(|chunk| {
+                let position = u64::decode(&chunk[..u64::SIZE])?;
+                Some((
+                    usize::try_from(position).ok()?,
+                    T::decode(&chunk[u64::SIZE..])?,
+                ))
+            })
+            .collect::<Option<Vec<(usize, T)>>>()
+            .ok_or(SnapshotError::Malformed("position does not fit in memory"))?;
+        SlidingWindow::restore(header.direction, window, next, items)
+            .map_err(SnapshotError::Malformed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{xorshift, POLICIES};
+    use crate::{
+        ArrayDeque, Direction, FloatOrder, MonotonicQueue, NanPolicy, Policy, SlidingWindow,
+        SnapshotError,
+    };
+
+    #[test]
+    fn snapshot_queue_round_trip() {
+        for direction in [Direction::Max, Direction::Min] {
+            for policy in POLICIES {
+                let mut mq = MonotonicQueue::with_direction(direction).with_policy(policy);
+                mq.extend([4i16, -7, 4, 9, 1, 1, -3]);
+
+                let bytes = mq.to_bytes();
+                assert_eq!(bytes.len(), 33 + 2 * mq.len());
+                let restored: MonotonicQueue<i16> = MonotonicQueue::from_bytes(&bytes).unwrap();
+                assert_eq!(restored, mq);
+                assert_eq!(restored.direction(), direction);
+                assert_eq!(restored.to_bytes(), bytes);
+            }
+        }
+
+        let empty: MonotonicQueue<u8> = MonotonicQueue::max();
+        assert_eq!(
+            MonotonicQueue::<u8>::from_bytes(&empty.to_bytes()),
+            Ok(empty)
+        );
+    }
+
+    #[test]
+    fn snapshot_window_round_trip() {
+        let mut sw = SlidingWindow::min(4);
+        for n in [5u64, 1, 4, 2, 3, 8] {
+            sw.push(n);
+        }
+
+        let bytes = sw.to_bytes();
+        let mut restored = SlidingWindow::<u64>::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.to_bytes(), bytes);
+        assert_eq!(restored.window(), 4);
+        assert_eq!(restored.next_position(), 6);
+        for n in [9, 9, 9] {
+            sw.push(n);
+            restored.push(n);
+            assert_eq!(restored.peek(), sw.peek());
+        }
+    }
+
+    #[test]
+    fn snapshot_float_round_trip() {
+        for nan in [
+            NanPolicy::TotalOrder,
+            NanPolicy::Greatest,
+            NanPolicy::Least,
+            NanPolicy::Reject,
+            NanPolicy::Skip,
+        ] {
+            let mut mq = MonotonicQueue::min_float(nan).with_policy(Policy::KeepEqualOldest);
+            for n in [2.5f32, -0.0, f32::NAN, 0.0, 7.25, f32::INFINITY] {
+                mq.try_push(n).ok();
+            }
+
+            let bytes = mq.to_bytes();
+            let restored: MonotonicQueue<f32, FloatOrder> =
+                MonotonicQueue::from_bytes(&bytes).unwrap();
+            assert_eq!(restored.comparator(), &FloatOrder::min(nan));
+            assert_eq!(restored.policy(), Policy::KeepEqualOldest);
+            assert!(restored
+                .iter()
+                .map(|n| n.to_bits())
+                .eq(mq.iter().map(|n| n.to_bits())));
+            assert_eq!(restored.to_bytes(), bytes);
+        }
+
+        let mut mq = MonotonicQueue::max_float(NanPolicy::Greatest);
+        mq.extend([f64::NAN, 1.0]);
+        let mut bytes = mq.to_bytes();
+        let decode = |bytes: &[u8]| MonotonicQueue::<f64, FloatOrder>::from_bytes(bytes);
+        assert!(matches!(
+            MonotonicQueue::<u64>::from_bytes(&bytes),
+            Err(SnapshotError::Malformed("snapshot kind mismatch"))
+        ));
+        bytes[6] = 3 << 1;
+        assert!(matches!(
+            decode(&bytes),
+            Err(SnapshotError::Malformed("snapshot holds a refused NaN"))
+        ));
+        bytes[6] = 5 << 1;
+        assert!(matches!(
+            decode(&bytes),
+            Err(SnapshotError::Malformed("unknown NaN policy"))
+        ));
+        bytes[6] = 2 << 1;
+        assert!(matches!(decode(&bytes), Err(SnapshotError::OutOfOrder(_))));
+
+        let mut bytes = MonotonicQueue::<u64>::max().to_bytes();
+        bytes[6] = 1 << 1;
+        assert!(matches!(
+            MonotonicQueue::<u64>::from_bytes(&bytes),
+            Err(SnapshotError::Malformed("snapshot holds a NaN policy"))
+        ));
+    }
+
+    #[test]
+    fn snapshot_rejects_malformed_input() {
+        let mut mq = MonotonicQueue::max();
+        mq.extend([9u32, 5, 3]);
+        let bytes = mq.to_bytes();
+        let decode = |bytes: &[u8]| MonotonicQueue::<u32>::from_bytes(bytes);
+
+        let mut future = bytes.clone();
+        future[4] = 2;
+        assert_eq!(decode(&future), Err(SnapshotError::UnsupportedVersion(2)));
+
+        assert_eq!(decode(b"not a snapshot"), Err(SnapshotError::BadMagic));
+        assert_eq!(
+            decode(&bytes[..bytes.len() - 1]),
+            Err(SnapshotError::Truncated)
+        );
+        assert_eq!(decode(&bytes[..20]), Err(SnapshotError::Truncated));
+        assert!(matches!(
+            decode(&[&bytes[..], &[0]].concat()),
+            Err(SnapshotError::Malformed(_))
+        ));
+        assert!(matches!(
+            MonotonicQueue::<u64>::from_bytes(&bytes),
+            Err(SnapshotError::Malformed(_))
+        ));
+        assert!(matches!(
+            SlidingWindow::<u32>::from_bytes(&bytes),
+            Err(SnapshotError::Malformed(_))
+        ));
+        assert!(matches!(
+            MonotonicQueue::<u32, Direction, ArrayDeque<u32, 2>>::from_bytes(&bytes),
+            Err(SnapshotError::Malformed(_))
+        ));
+
+        let mut sw = SlidingWindow::max(3);
+        for n in [1u32, 2, 3, 4, 5] {
+            sw.push(n);
+        }
+        let bytes = sw.to_bytes();
+        let decode = |bytes: &[u8]| SlidingWindow::<u32>::from_bytes(bytes);
+        assert!(decode(&bytes).is_ok());
+        let mut emptied = bytes[..33].to_vec();
+        emptied[25..33].copy_from_slice(&0u64.to_le_bytes());
+        let mut stale = bytes.clone();
+        stale[33..41].copy_from_slice(&2u64.to_le_bytes());
+        let mut unbounded = bytes.clone();
+        unbounded[9..17].copy_from_slice(&u64::MAX.to_le_bytes());
+        let mut restored = decode(&unbounded).unwrap();
+        for n in [9, 0] {
+            restored.push(n);
+        }
+        assert_eq!(restored.peek(), Some(&9));
+
+        for corrupted in [emptied, stale] {
+            assert!(matches!(
+                decode(&corrupted),
+                Err(SnapshotError::Malformed("snapshot lost the newest element"))
+            ));
+        }
+
+        let bytes = mq.to_bytes();
+        let decode = |bytes: &[u8]| MonotonicQueue::<u32>::from_bytes(bytes);
+        let mut swapped = bytes.clone();
+        swapped[33..37].copy_from_slice(&3u32.to_le_bytes());
+        swapped[41..45].copy_from_slice(&9u32.to_le_bytes());
+        let error = decode(&swapped).unwrap_err();
+        assert!(matches!(error, SnapshotError::OutOfOrder(_)));
+        assert_eq!(
+            error.to_string(),
+            "malformed snapshot: elements at 0 and 1 are out of monotonic order"
+        );
+    }
+
+    #[test]
+    fn snapshot_fuzz_malformed_input() {
+        let mut seed = 0x9e37_79b9_u32;
+        let mut mq = MonotonicQueue::min().with_policy(Policy::KeepEqualOldest);
+        let mut sw = SlidingWindow::max(5);
+        for _ in 0..40 {
+            let n = xorshift(&mut seed) % 50;
+            mq.push(n);
+            sw.push(n);
+        }
+        let samples = [mq.to_bytes(), sw.to_bytes()];
+
+        for _ in 0..20_000 {
+            let mut bytes = samples[xorshift(&mut seed) as usize % 2].clone();
+            for _ in 0..1 + xorshift(&mut seed) % 4 {
+                let index = xorshift(&mut seed) as usize % bytes.len();
+                match xorshift(&mut seed) % 4 {
+                    0 => bytes[index] ^= 1 << (xorshift(&mut seed) % 8),
+                    1 => bytes[index] = xorshift(&mut seed) as u8,
+                    2 => bytes.truncate(index),
+                    _ => bytes.insert(index, xorshift(&mut seed) as u8),
+                }
+                if bytes.is_empty() {
+                    break;
+                }
+            }
+
+            if let Ok(mq) = MonotonicQueue::<u32>::from_bytes(&bytes) {
+                assert_eq!(mq.validate(), Ok(()));
+                assert_eq!(mq.to_bytes(), bytes);
+            }
+            if let Ok(sw) = SlidingWindow::<u32>::from_bytes(&bytes) {
+                assert_eq!(sw.to_bytes(), bytes);
+            }
+        }
+    }
+}
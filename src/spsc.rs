@@ -0,0 +1,202 @@
+use std::fmt;
+
+use crate::sync::{Arc, AtomicU8, Ordering, UnsafeCell};
+use crate::{Compare, SlidingWindow};
+
+/// Set in the index of the shared slot when the producer published to it
+/// since the consumer last took it.
+const FRESH: u8 = 0b100;
+const INDEX: u8 = 0b011;
+
+/// Triple buffer the producer publishes the extremum through. At any time,
+/// one slot is owned by the producer, one by the consumer, and the third is
+/// the shared slot whose index is held by `shared`; ownership changes hands
+/// only through swaps of `shared`.
+struct Slots<T> {
+    slots: [UnsafeCell<Option<T>>; 3],
+    shared: AtomicU8,
+}
+
+// Safety: a slot is accessed only by the thread owning it, and is handed over
+// to the other thread by a release swap of `shared`, acquired by the other.
+unsafe impl<T: Send> Sync for Slots<T> {}
+
+/// Create a single-producer, single-consumer channel over `sw`: the producer
+/// pushes to the window, and the consumer reads the extremum of the window as
+/// of the producer's latest push.
+///
+/// Neither end ever waits for the other: each push publishes a clone of the
+/// new extremum, and the consumer reads the latest published one wait-free.
+///
+/// # Example
+/// ```
+/// use std::thread;
+///
+/// use monotonicqueue::{window_channel, SlidingWindow};
+///
+/// let (mut producer, mut consumer) = window_channel(SlidingWindow::max(3));
+///
+/// thread::spawn(move || {
+///     for tick in [4, 2, 3, 1, 0] {
+///         producer.push(tick);
+///     }
+/// })
+/// .join()
+/// .unwrap();
+///
+/// assert_eq!(consumer.peek(), Some(&3));
+/// ```
+pub fn window_channel<T: Clone, C: Compare<T>>(
+    sw: SlidingWindow<T, C>,
+) -> (WindowProducer<T, C>, WindowConsumer<T>) {
+    let slots = Arc::new(Slots {
+        slots: [
+            UnsafeCell::new(None),
+            UnsafeCell::new(None),
+            UnsafeCell::new(None),
+        ],
+        shared: AtomicU8::new(1),
+    });
+    let mut producer = WindowProducer {
+        sw,
+        slots: slots.clone(),
+        write: 0,
+    };
+    // The window may not be empty to begin with.
+    producer.publish();
+    let consumer = WindowConsumer { slots, read: 2 };
+    (producer, consumer)
+}
+
+/// The pushing end of a [`window_channel`].
+pub struct WindowProducer<T, C> {
+    sw: SlidingWindow<T, C>,
+    slots: Arc<Slots<T>>,
+    write: u8,
+}
+
+impl<T: Clone, C: Compare<T>> WindowProducer<T, C> {
+    /// Returns the window pushed to.
+    pub fn window(&self) -> &SlidingWindow<T, C> {
+        &self.sw
+    }
+
+    /// Pushes `item` to the window, as
+    /// [`SlidingWindow::push`](SlidingWindow::push) does, and publishes the
+    /// new extremum to the consumer.
+    pub fn push(&mut self, item: T) {
+        self.sw.push(item);
+        self.publish();
+    }
+
+    /// Writes the extremum to the producer's slot, then swaps it with the
+    /// shared slot.
+    fn publish(&mut self) {
+        let extremum = self.sw.peek().cloned();
+        self.slots.slots[usize::from(self.write)].with_mut(|slot| {
+            // Safety: the producer owns its slot.
+            unsafe { *slot = extremum }
+        });
+        let shared = self.slots.shared.swap(self.write | FRESH, Ordering::AcqRel);
+        self.write = shared & INDEX;
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for WindowProducer<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WindowProducer")
+            .field("peek", &self.sw.peek())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The reading end of a [`window_channel`].
+pub struct WindowConsumer<T> {
+    slots: Arc<Slots<T>>,
+    read: u8,
+}
+
+impl<T> WindowConsumer<T> {
+    /// Provides a peek to the extremum of the window as of the latest push
+    /// published by the producer, or None if the window was empty.
+    ///
+    /// This never waits for the producer.
+    pub fn peek(&mut self) -> Option<&T> {
+        if self.slots.shared.load(Ordering::Relaxed) & FRESH != 0 {
+            // Only the consumer clears `FRESH`, so the shared slot is still
+            // fresh when swapped.
+            let shared = self.slots.shared.swap(self.read, Ordering::AcqRel);
+            self.read = shared & INDEX;
+        }
+        self.slots.slots[usize::from(self.read)].with(|slot| {
+            // Safety: the consumer owns its slot until the next call, which
+            // borrows `self` mutably.
+            unsafe { (*slot).as_ref() }
+        })
+    }
+}
+
+impl<T> fmt::Debug for WindowConsumer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WindowConsumer").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{window_channel, SlidingWindow};
+
+    #[cfg(not(loom))]
+    #[test]
+    fn window_channel_publishes_in_order() {
+        use std::thread;
+
+        const TICKS: usize = 100_000;
+        let (mut producer, mut consumer) = window_channel(SlidingWindow::min(8));
+        assert_eq!(consumer.peek(), None);
+
+        let handle = thread::spawn(move || {
+            for tick in 0..TICKS {
+                producer.push(tick);
+            }
+        });
+
+        let mut last = 0;
+        while last < TICKS - 8 {
+            if let Some(&min) = consumer.peek() {
+                assert!(last <= min && min <= TICKS - 8);
+                last = min;
+            }
+        }
+        handle.join().unwrap();
+    }
+
+    // Run with `RUSTFLAGS="--cfg loom" cargo test --release window_channel`.
+    #[cfg(loom)]
+    #[test]
+    fn window_channel_loom() {
+        loom::model(|| {
+            let mut sw = SlidingWindow::max(2);
+            sw.push(1);
+            let (mut producer, mut consumer) = window_channel(sw);
+
+            let handle = loom::thread::spawn(move || {
+                producer.push(5);
+                producer.push(4);
+                producer.push(3);
+            });
+
+            // The published extrema are 1, 5, 5, 4, in this order.
+            let mut seen = Vec::new();
+            for _ in 0..3 {
+                seen.extend(consumer.peek().copied());
+            }
+            handle.join().unwrap();
+            seen.extend(consumer.peek().copied());
+
+            let order = |n: &i32| [1, 5, 4].iter().position(|m| m == n).unwrap();
+            assert!(seen.windows(2).all(|w| order(&w[0]) <= order(&w[1])));
+            assert_eq!(seen.last(), Some(&4));
+        });
+    }
+}
@@ -0,0 +1,230 @@
+use std::fmt;
+
+use crate::storage::push_back;
+use crate::{Compare, Direction, Policy};
+
+/// Monotonic Stack is the stack counterpart of
+/// [`MonotonicQueue`](crate::MonotonicQueue): elements are pushed and popped
+/// at the top, and every push first pops the elements the new one dominates,
+/// which it returns.
+///
+/// This is the building block of next-greater-element and largest rectangle
+/// in a histogram algorithms. Elements are ordered by the same comparators and
+/// [`Policy`] as the queue's, the top holding the most recent element.
+///
+/// # Example
+/// ```
+/// use monotonicqueue::MonotonicStack;
+///
+/// // Next greater element of every value, by index.
+/// let values = [2, 1, 2, 4, 3];
+/// let mut next_greater = [None; 5];
+///
+/// let mut stack = MonotonicStack::with_comparator(|&i: &usize, &j: &usize| values[i] < values[j]);
+/// for index in 0..values.len() {
+///     for popped in stack.push(index) {
+///         next_greater[popped] = Some(values[index]);
+///     }
+/// }
+///
+/// assert_eq!(next_greater, [Some(4), Some(2), Some(4), None, None]);
+/// ```
+#[derive(Clone)]
+pub struct MonotonicStack<T, C = Direction> {
+    stack: Vec<T>,
+    cmp: C,
+    policy: Policy,
+}
+
+impl<T> MonotonicStack<T> {
+    /// Create an empty monotonic stack whose bottom holds the maximum.
+    pub fn max() -> MonotonicStack<T> {
+        MonotonicStack::with_comparator(Direction::Max)
+    }
+
+    /// Create an empty monotonic stack whose bottom holds the minimum.
+    pub fn min() -> MonotonicStack<T> {
+        MonotonicStack::with_comparator(Direction::Min)
+    }
+
+    /// Create an empty monotonic stack ordered in `direction`.
+    pub fn with_direction(direction: Direction) -> MonotonicStack<T> {
+        MonotonicStack::with_comparator(direction)
+    }
+}
+
+impl<T, C> MonotonicStack<T, C> {
+    /// Create an empty monotonic stack that stores `cmp` and applies it on
+    /// every `push`.
+    pub fn with_comparator(cmp: C) -> MonotonicStack<T, C> {
+        MonotonicStack {
+            stack: Vec::new(),
+            cmp,
+            policy: Policy::NonStrict,
+        }
+    }
+
+    /// Sets the policy applied to equal elements on subsequent pushes.
+    pub fn with_policy(mut self, policy: Policy) -> MonotonicStack<T, C> {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the comparator applied on `push`.
+    pub fn comparator(&self) -> &C {
+        &self.cmp
+    }
+
+    /// Returns the policy applied to equal elements.
+    pub fn policy(&self) -> Policy {
+        self.policy
+    }
+
+    /// Provides a peek to the top element, or None.
+    pub fn peek(&self) -> Option<&T> {
+        self.stack.last()
+    }
+
+    /// Returns the number of retained elements.
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns true if the stack retains no element.
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    /// Returns the retained elements, from bottom to top.
+    pub fn as_slice(&self) -> &[T] {
+        &self.stack
+    }
+
+    /// Removes the top element and returns it, or None if empty.
+    pub fn pop(&mut self) -> Option<T> {
+        self.stack.pop()
+    }
+
+    /// Pushes `item` to the top, popping the elements for which
+    /// `is_less(existing_item, &item)` holds, and returns them, top first.
+    /// Under [`Policy::KeepEqualOldest`], a refused `item` is returned as well.
+    ///
+    /// The stored comparator is ignored, as by
+    /// [`MonotonicQueue::push_by`](crate::MonotonicQueue::push_by).
+    pub fn push_by<F>(&mut self, item: T, is_less: F) -> Vec<T>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let mut popped = Vec::new();
+        push_back(
+            &mut self.stack,
+            self.policy,
+            item,
+            |existing_item, item| is_less(existing_item, item),
+            |existing_item, item| is_less(item, existing_item),
+            |item| popped.push(item),
+        )
+        .ok();
+        popped
+    }
+}
+
+impl<T, C: Compare<T>> MonotonicStack<T, C> {
+    /// Pushes `item` to the top, popping the elements it dominates under the
+    /// stack's comparator, and returns them, top first. Under
+    /// [`Policy::KeepEqualOldest`], a refused `item` is returned as well.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::MonotonicStack;
+    ///
+    /// let mut stack = MonotonicStack::min();
+    /// stack.push(1);
+    /// stack.push(4);
+    /// stack.push(5);
+    ///
+    /// assert_eq!(stack.push(3), vec![5, 4]);
+    /// assert_eq!(stack.as_slice(), &[1, 3]);
+    /// ```
+    pub fn push(&mut self, item: T) -> Vec<T> {
+        let cmp = &self.cmp;
+        let mut popped = Vec::new();
+        push_back(
+            &mut self.stack,
+            self.policy,
+            item,
+            |existing_item, item| cmp.is_less(existing_item, item),
+            |existing_item, item| cmp.is_less(item, existing_item),
+            |item| popped.push(item),
+        )
+        .ok();
+        popped
+    }
+}
+
+impl<T, C: Default> Default for MonotonicStack<T, C> {
+    fn default() -> MonotonicStack<T, C> {
+        MonotonicStack::with_comparator(C::default())
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for MonotonicStack<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MonotonicStack")
+            .field("stack", &self.stack)
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MonotonicStack, Policy};
+
+    #[test]
+    fn monotonic_stack_largest_rectangle() {
+        let heights = [2, 1, 5, 6, 2, 3, 0];
+        let mut stack =
+            MonotonicStack::with_comparator(|&i: &usize, &j: &usize| heights[i] > heights[j]);
+
+        let mut largest = 0;
+        for index in 0..heights.len() {
+            let popped = stack.push(index);
+            let retained = stack.as_slice();
+            let floor = retained.len().checked_sub(2).map(|below| retained[below]);
+            for (rank, &top) in popped.iter().enumerate() {
+                let left = popped
+                    .get(rank + 1)
+                    .copied()
+                    .or(floor)
+                    .map_or(0, |below| below + 1);
+                largest = largest.max(heights[top] * (index - left));
+            }
+        }
+
+        assert_eq!(largest, 10);
+    }
+
+    #[test]
+    fn monotonic_stack_policies() {
+        let mut stack = MonotonicStack::max().with_policy(Policy::KeepEqualOldest);
+        assert!(stack.push(3).is_empty());
+        assert_eq!(stack.push(3), vec![3]);
+        assert_eq!(stack.push(5), vec![3]);
+
+        let mut stack = MonotonicStack::max().with_policy(Policy::KeepEqualNewest);
+        stack.push(3);
+        assert_eq!(stack.push(3), vec![3]);
+        assert_eq!(stack.len(), 1);
+        assert_eq!(
+            format!("{:?}", stack),
+            "MonotonicStack { stack: [3], policy: KeepEqualNewest, .. }"
+        );
+
+        let mut stack = MonotonicStack::min();
+        assert!(stack.push_by(2, |n1: &i32, n2: &i32| n1 < n2).is_empty());
+        assert_eq!(stack.push_by(4, |n1, n2| n1 < n2), vec![2]);
+        assert_eq!(stack.pop(), Some(4));
+        assert!(stack.is_empty());
+    }
+}
@@ -0,0 +1,255 @@
+#[cfg(feature = "std")]
+use std::collections::VecDeque;
+
+use crate::invariant::{check_push, check_push_front};
+use crate::Policy;
+
+/// Double-ended storage backing a monotonic queue.
+///
+/// Implemented for [`VecDeque`] (the default), [`Vec`], and for the inline, fixed
+/// capacity [`ArrayDeque`](crate::ArrayDeque). Any storage can be plugged into
+/// [`MonotonicQueue`](crate::MonotonicQueue) with `with_storage`; it must
+/// behave like a `VecDeque`, which `check_conformance`, enabled by the
+/// `testing` feature, checks.
+pub trait DequeStorage<T> {
+    /// Returns the number of stored elements.
+    fn len(&self) -> usize;
+
+    /// Returns the element at index `index` from the front, or None if it is
+    /// out of bounds.
+    fn get(&self, index: usize) -> Option<&T>;
+
+    /// Appends `item` at the back, or gives it back if the storage is full.
+    fn push_back(&mut self, item: T) -> Result<(), T>;
+
+    /// Prepends `item` at the front, or gives it back if the storage is full.
+    fn push_front(&mut self, item: T) -> Result<(), T>;
+
+    /// Removes the back element and returns it, or None if empty.
+    fn pop_back(&mut self) -> Option<T>;
+
+    /// Removes the front element and returns it, or None if empty.
+    fn pop_front(&mut self) -> Option<T>;
+
+    /// Returns true if no element is stored.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Provides a peek to the front element, or None.
+    fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Provides a peek to the back element, or None.
+    fn back(&self) -> Option<&T> {
+        self.len().checked_sub(1).and_then(|index| self.get(index))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> DequeStorage<T> for VecDeque<T> {
+    fn len(&self) -> usize {
+        VecDeque::len(self)
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        VecDeque::get(self, index)
+    }
+
+    fn push_back(&mut self, item: T) -> Result<(), T> {
+        VecDeque::push_back(self, item);
+        Ok(())
+    }
+
+    fn push_front(&mut self, item: T) -> Result<(), T> {
+        VecDeque::push_front(self, item);
+        Ok(())
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        VecDeque::pop_back(self)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        VecDeque::pop_front(self)
+    }
+
+    fn front(&self) -> Option<&T> {
+        VecDeque::front(self)
+    }
+
+    fn back(&self) -> Option<&T> {
+        VecDeque::back(self)
+    }
+}
+
+/// Vectors are stacks: `push_front` and `pop_front` shift every element and
+/// run in linear time, which [`MonotonicStack`](crate::MonotonicStack) never calls.
+#[cfg(feature = "std")]
+impl<T> DequeStorage<T> for Vec<T> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        <[T]>::get(self, index)
+    }
+
+    fn push_back(&mut self, item: T) -> Result<(), T> {
+        Vec::push(self, item);
+        Ok(())
+    }
+
+    fn push_front(&mut self, item: T) -> Result<(), T> {
+        Vec::insert(self, 0, item);
+        Ok(())
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        Vec::pop(self)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.remove(0))
+        }
+    }
+
+    fn back(&self) -> Option<&T> {
+        <[T]>::last(self)
+    }
+}
+
+/// Pushes `item` to the back of `dq` under `policy`, the dominance rule shared
+/// by every monotonic queue of this crate.
+///
+/// Both predicates are called as `f(existing_item, &item)`: `is_below` tells
+/// whether the existing item is dominated by `item`, `is_above` whether `item`
+/// is dominated by the existing item. Every element popped from `dq`, and
+/// `item` itself if the policy refuses it, is handed to `evicted`. Returns
+/// whether `item` was stored, or `Err(item)` if `dq` is full once the
+/// dominated elements were popped.
+///
+/// # Panics
+/// Panics if the push leaves `dq` out of order, see
+/// [`check_push`](crate::invariant::check_push).
+pub(crate) fn push_back<T, S, F, G, E>(
+    dq: &mut S,
+    policy: Policy,
+    item: T,
+    is_below: F,
+    is_above: G,
+    mut evicted: E,
+) -> Result<bool, T>
+where
+    S: DequeStorage<T>,
+    F: Fn(&T, &T) -> bool,
+    G: Fn(&T, &T) -> bool,
+    E: FnMut(T),
+{
+    while let Some(existing_item) = dq.back() {
+        let dominated = match policy {
+            Policy::NonStrict | Policy::KeepEqualOldest => is_below(existing_item, &item),
+            Policy::KeepEqualNewest => !is_above(existing_item, &item),
+        };
+        if dominated {
+            if let Some(existing_item) = dq.pop_back() {
+                evicted(existing_item);
+            }
+        } else {
+            break;
+        }
+    }
+    if policy == Policy::KeepEqualOldest {
+        if let Some(existing_item) = dq.back() {
+            if !is_above(existing_item, &item) {
+                evicted(item);
+                return Ok(false);
+            }
+        }
+    }
+    dq.push_back(item)?;
+    check_push(dq, policy, is_below, is_above);
+    Ok(true)
+}
+
+/// Pushes `item` to the front of `dq` under `policy`, as if it had been pushed
+/// to the back before every element that led to the current content of `dq`.
+///
+/// `item` is older than every element, so it pops none of them: it is stored
+/// if the front does not dominate it, and handed back as `Err(item)`
+/// otherwise. Equal to the front, it is stored under [`Policy::NonStrict`],
+/// refused under [`Policy::KeepEqualNewest`], and replaces the front, which is
+/// handed to `evicted`, under [`Policy::KeepEqualOldest`]. The predicates are
+/// those of [`push_back`].
+///
+/// # Panics
+/// Panics if the storage is full, or if the push leaves `dq` out of order, see
+/// [`check_push_front`](crate::invariant::check_push_front).
+pub(crate) fn push_front<T, S, F, G, E>(
+    dq: &mut S,
+    policy: Policy,
+    item: T,
+    is_below: F,
+    is_above: G,
+    mut evicted: E,
+) -> Result<(), T>
+where
+    S: DequeStorage<T>,
+    F: Fn(&T, &T) -> bool,
+    G: Fn(&T, &T) -> bool,
+    E: FnMut(T),
+{
+    if let Some(front) = dq.front() {
+        if is_below(&item, front) {
+            return Err(item);
+        }
+        if !is_above(&item, front) {
+            match policy {
+                Policy::NonStrict => {}
+                Policy::KeepEqualOldest => {
+                    if let Some(front) = dq.pop_front() {
+                        evicted(front);
+                    }
+                }
+                Policy::KeepEqualNewest => return Err(item),
+            }
+        }
+    }
+    if dq.push_front(item).is_err() {
+        panic!("monotonic queue storage is full");
+    }
+    check_push_front(dq, policy, is_below, is_above);
+    Ok(())
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::collections::VecDeque;
+
+    use crate::persistent::PersistentDeque;
+    use crate::testing::check_conformance;
+    use crate::ArrayDeque;
+
+    #[test]
+    fn vec_conformance() {
+        check_conformance(VecDeque::new(), usize::MAX);
+        check_conformance(Vec::new(), usize::MAX);
+    }
+
+    #[test]
+    fn persistent_deque_conformance() {
+        check_conformance(PersistentDeque::new(), usize::MAX);
+    }
+
+    #[test]
+    fn array_deque_conformance() {
+        check_conformance(ArrayDeque::<_, 0>::new(), 0);
+        check_conformance(ArrayDeque::<_, 1>::new(), 1);
+        check_conformance(ArrayDeque::<_, 5>::new(), 5);
+        check_conformance(ArrayDeque::<_, 64>::new(), 64);
+    }
+}
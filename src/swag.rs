@@ -0,0 +1,269 @@
+use std::marker::PhantomData;
+use std::ops;
+
+/// An associative operator with an identity element, aggregated over windows
+/// by a [`SlidingAggregator`].
+///
+/// `combine` must be associative, but need not be commutative: aggregates are
+/// always combined from the oldest element to the newest.
+pub trait Monoid {
+    /// The type of elements and aggregates.
+    type Item;
+
+    /// Returns the identity element, i.e. the aggregate of an empty window.
+    fn identity(&self) -> Self::Item;
+
+    /// Combines an older aggregate `n1` with a newer aggregate `n2`.
+    fn combine(&self, n1: &Self::Item, n2: &Self::Item) -> Self::Item;
+}
+
+/// Sum monoid, whose identity is `T::default()`.
+pub struct Sum<T>(PhantomData<fn(T) -> T>);
+
+impl<T> Sum<T> {
+    /// Create a sum monoid.
+    pub fn new() -> Sum<T> {
+        Sum(PhantomData)
+    }
+}
+
+impl<T> Default for Sum<T> {
+    fn default() -> Sum<T> {
+        Sum::new()
+    }
+}
+
+impl<T> Monoid for Sum<T>
+where
+    T: Default,
+    for<'a> &'a T: ops::Add<Output = T>,
+{
+    type Item = T;
+
+    fn identity(&self) -> T {
+        T::default()
+    }
+
+    fn combine(&self, n1: &T, n2: &T) -> T {
+        n1 + n2
+    }
+}
+
+/// Bitwise or monoid, whose identity is `T::default()`.
+pub struct BitOr<T>(PhantomData<fn(T) -> T>);
+
+impl<T> BitOr<T> {
+    /// Create a bitwise or monoid.
+    pub fn new() -> BitOr<T> {
+        BitOr(PhantomData)
+    }
+}
+
+impl<T> Default for BitOr<T> {
+    fn default() -> BitOr<T> {
+        BitOr::new()
+    }
+}
+
+impl<T> Monoid for BitOr<T>
+where
+    T: Default,
+    for<'a> &'a T: ops::BitOr<Output = T>,
+{
+    type Item = T;
+
+    fn identity(&self) -> T {
+        T::default()
+    }
+
+    fn combine(&self, n1: &T, n2: &T) -> T {
+        n1 | n2
+    }
+}
+
+/// Sliding Aggregator maintains the aggregate of the last `window` pushed
+/// elements under an arbitrary [`Monoid`], in O(1) amortized time per push.
+///
+/// Where a [`MonotonicQueue`](crate::MonotonicQueue) only answers min and max,
+/// this answers any associative operator (sum, gcd, bitwise or, matrix
+/// product, ...). It is implemented with two stacks: pushes go onto the back
+/// stack, which keeps a running aggregate, and evictions pop the front stack,
+/// which stores each element alongside the aggregate of itself and every newer
+/// element on that stack. The back stack is flipped onto the front stack when
+/// the latter runs empty.
+pub struct SlidingAggregator<M: Monoid> {
+    monoid: M,
+    front: Vec<(M::Item, M::Item)>,
+    back: Vec<M::Item>,
+    back_agg: M::Item,
+    window: usize,
+}
+
+impl<M: Monoid> SlidingAggregator<M> {
+    /// Create an empty sliding aggregator over the last `window` elements.
+    ///
+    /// # Panics
+    /// Panics if `window` is zero.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::{SlidingAggregator, Sum};
+    ///
+    /// let mut sa = SlidingAggregator::new(3, Sum::new());
+    /// for n in [1, 2, 3, 4] {
+    ///     sa.push(n);
+    /// }
+    ///
+    /// assert_eq!(sa.query(), 9);
+    /// ```
+    pub fn new(window: usize, monoid: M) -> SlidingAggregator<M> {
+        assert!(window > 0, "window length must be non-zero");
+        SlidingAggregator {
+            back_agg: monoid.identity(),
+            monoid,
+            front: Vec::new(),
+            back: Vec::new(),
+            window,
+        }
+    }
+
+    /// Returns the window length.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Returns the number of elements in the window.
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    /// Returns true if the window holds no element.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the aggregate of the window, from its oldest element to its
+    /// newest, or the identity if it is empty.
+    pub fn query(&self) -> M::Item {
+        match self.front.last() {
+            Some((_, front_agg)) => self.monoid.combine(front_agg, &self.back_agg),
+            None => self.monoid.combine(&self.monoid.identity(), &self.back_agg),
+        }
+    }
+
+    /// Pushes `item` as the newest element, evicting the oldest one if the
+    /// window is full.
+    pub fn push(&mut self, item: M::Item) {
+        if self.len() == self.window {
+            self.pop();
+        }
+        self.back_agg = self.monoid.combine(&self.back_agg, &item);
+        self.back.push(item);
+    }
+
+    /// Removes the oldest element of the window and returns it, or None if
+    /// it is empty.
+    pub fn pop(&mut self) -> Option<M::Item> {
+        if self.front.is_empty() {
+            self.flip();
+        }
+        self.front.pop().map(|(item, _)| item)
+    }
+
+    /// Moves the back stack onto the front stack, so that the oldest element
+    /// ends up on top.
+    fn flip(&mut self) {
+        while let Some(item) = self.back.pop() {
+            let agg = match self.front.last() {
+                Some((_, newer_agg)) => self.monoid.combine(&item, newer_agg),
+                None => self.monoid.combine(&item, &self.monoid.identity()),
+            };
+            self.front.push((item, agg));
+        }
+        self.back_agg = self.monoid.identity();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BitOr, Monoid, SlidingAggregator, Sum};
+
+    struct Gcd;
+
+    impl Monoid for Gcd {
+        type Item = u64;
+
+        fn identity(&self) -> u64 {
+            0
+        }
+
+        fn combine(&self, n1: &u64, n2: &u64) -> u64 {
+            let (mut a, mut b) = (*n1, *n2);
+            while b != 0 {
+                (a, b) = (b, a % b);
+            }
+            a
+        }
+    }
+
+    struct Concat;
+
+    impl Monoid for Concat {
+        type Item = String;
+
+        fn identity(&self) -> String {
+            String::new()
+        }
+
+        fn combine(&self, n1: &String, n2: &String) -> String {
+            format!("{}{}", n1, n2)
+        }
+    }
+
+    #[test]
+    fn sliding_sum_and_bit_or() {
+        let mut sum = SlidingAggregator::new(3, Sum::new());
+        let mut or = SlidingAggregator::new(2, BitOr::new());
+
+        let mut sums = Vec::new();
+        let mut ors = Vec::new();
+        for n in [1u32, 2, 4, 8, 16] {
+            sum.push(n);
+            or.push(n);
+            sums.push(sum.query());
+            ors.push(or.query());
+        }
+
+        assert_eq!(sums, vec![1, 3, 7, 14, 28]);
+        assert_eq!(ors, vec![1, 3, 6, 12, 24]);
+    }
+
+    #[test]
+    fn sliding_gcd() {
+        let mut sa = SlidingAggregator::new(2, Gcd);
+
+        let mut gcds = Vec::new();
+        for n in [12, 18, 27, 9, 5] {
+            sa.push(n);
+            gcds.push(sa.query());
+        }
+
+        assert_eq!(gcds, vec![12, 6, 9, 9, 1]);
+    }
+
+    #[test]
+    fn sliding_aggregate_keeps_order() {
+        let mut sa = SlidingAggregator::new(3, Concat);
+
+        let mut concats = Vec::new();
+        for s in ["a", "b", "c", "d", "e"] {
+            sa.push(s.to_string());
+            concats.push(sa.query());
+        }
+
+        assert_eq!(concats, vec!["a", "ab", "abc", "bcd", "cde"]);
+        assert_eq!(sa.pop().as_deref(), Some("c"));
+        assert_eq!(sa.query(), "de");
+        assert_eq!(sa.len(), 2);
+    }
+}
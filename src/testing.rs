@@ -0,0 +1,78 @@
+use std::collections::VecDeque;
+
+use crate::DequeStorage;
+#[cfg(test)]
+use crate::Policy;
+
+/// Every [`Policy`], for the tests covering each of them.
+#[cfg(test)]
+pub(crate) const POLICIES: [Policy; 3] = [
+    Policy::NonStrict,
+    Policy::KeepEqualOldest,
+    Policy::KeepEqualNewest,
+];
+
+/// Advances the xorshift generator `seed`, which must be non-zero, and returns
+/// its new value.
+pub(crate) fn xorshift(seed: &mut u32) -> u32 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 17;
+    *seed ^= *seed << 5;
+    *seed
+}
+
+/// Drives `storage` through a pseudo-random sequence of operations and checks
+/// every observation against a `VecDeque` holding at most `capacity` elements.
+///
+/// Every [`DequeStorage`] must pass it: run it from the tests of your own
+/// storage, with the `testing` feature enabled.
+///
+/// # Panics
+/// Panics if `storage` is not empty, or as soon as it departs from the
+/// `VecDeque`.
+///
+/// # Example
+/// ```
+/// use std::collections::VecDeque;
+///
+/// monotonicqueue::check_conformance(VecDeque::new(), usize::MAX);
+/// ```
+pub fn check_conformance<S: DequeStorage<u32>>(mut storage: S, capacity: usize) {
+    let mut model = VecDeque::new();
+    let mut seed = 0x2545_f491_u32;
+
+    assert!(storage.is_empty());
+    for n in 0..2000 {
+        let seed = xorshift(&mut seed);
+        match seed % 6 {
+            0 | 1 => {
+                let pushed = storage.push_back(n);
+                if model.len() < capacity {
+                    assert_eq!(pushed, Ok(()));
+                    model.push_back(n);
+                } else {
+                    assert_eq!(pushed, Err(n));
+                }
+            }
+            5 => {
+                let pushed = storage.push_front(n);
+                if model.len() < capacity {
+                    assert_eq!(pushed, Ok(()));
+                    model.push_front(n);
+                } else {
+                    assert_eq!(pushed, Err(n));
+                }
+            }
+            2 => assert_eq!(storage.pop_back(), model.pop_back()),
+            3 => assert_eq!(storage.pop_front(), model.pop_front()),
+            _ => {
+                let index = (seed >> 8) as usize % (model.len() + 1);
+                assert_eq!(storage.get(index), model.get(index));
+            }
+        }
+        assert_eq!(storage.len(), model.len());
+        assert_eq!(storage.is_empty(), model.is_empty());
+        assert_eq!(storage.front(), model.front());
+        assert_eq!(storage.back(), model.back());
+    }
+}
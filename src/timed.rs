@@ -0,0 +1,380 @@
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "serde")]
+use serde::de::Error as _;
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::Policy;
+use crate::{Compare, Direction, MonotonicQueue};
+
+/// A point on a monotonic clock, used to timestamp the elements of a
+/// [`TimedWindow`].
+///
+/// Implemented for [`Instant`] and for `u64` ticks. Implement it for your own
+/// clock type to drive a window from a fake clock in tests.
+pub trait Timestamp: Copy + Ord {
+    /// The length of a time window on this clock.
+    type Span: Copy;
+
+    /// Returns the first point in time at which an element stamped with
+    /// `self` is out of a window of length `span`, or None if it never is.
+    fn expires_at(self, span: Self::Span) -> Option<Self>;
+}
+
+impl Timestamp for Instant {
+    type Span = Duration;
+
+    fn expires_at(self, span: Duration) -> Option<Instant> {
+        self.checked_add(span)
+    }
+}
+
+impl Timestamp for u64 {
+    type Span = u64;
+
+    fn expires_at(self, span: u64) -> Option<u64> {
+        self.checked_add(span)
+    }
+}
+
+/// Timed Window is a monotonic queue over the elements pushed during the last
+/// `span` of time.
+///
+/// Each element is stored alongside the timestamp it was pushed at. Calling
+/// `advance_to(now)` evicts the front elements stamped at or before
+/// `now - span`, so `peek` returns the extremum of the time window ending at
+/// `now`. Timestamps are expected to be pushed in non-decreasing order, which
+/// debug builds check.
+pub struct TimedWindow<T, I: Timestamp = Instant, C = Direction> {
+    mq: MonotonicQueue<(I, T)>,
+    cmp: C,
+    span: I::Span,
+}
+
+impl<T, I: Timestamp> TimedWindow<T, I> {
+    /// Create an empty timed window of length `span`.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use monotonicqueue::TimedWindow;
+    ///
+    /// let tw: TimedWindow<i32> = TimedWindow::new(Duration::from_secs(30));
+    /// ```
+    pub fn new(span: I::Span) -> TimedWindow<T, I> {
+        TimedWindow::with_comparator(span, Direction::Max)
+    }
+
+    /// Create an empty timed window whose `peek` returns the maximum of the
+    /// last `span` of time.
+    pub fn max(span: I::Span) -> TimedWindow<T, I> {
+        TimedWindow::with_comparator(span, Direction::Max)
+    }
+
+    /// Create an empty timed window whose `peek` returns the minimum of the
+    /// last `span` of time.
+    pub fn min(span: I::Span) -> TimedWindow<T, I> {
+        TimedWindow::with_comparator(span, Direction::Min)
+    }
+}
+
+impl<T, I: Timestamp, C> TimedWindow<T, I, C> {
+    /// Create an empty timed window of length `span`, applying `cmp` on every
+    /// `push`.
+    pub fn with_comparator(span: I::Span, cmp: C) -> TimedWindow<T, I, C> {
+        TimedWindow {
+            mq: MonotonicQueue::new(),
+            cmp,
+            span,
+        }
+    }
+
+    /// Returns the window length.
+    pub fn span(&self) -> I::Span {
+        self.span
+    }
+
+    /// Provides a peek to the extremum of the window, or None.
+    ///
+    /// The window is only moved forward by `advance_to` and `push_by`, so call
+    /// `advance_to` first when time has passed since the last push.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::TimedWindow;
+    ///
+    /// let mut tw = TimedWindow::<i32, u64>::new(10);
+    ///
+    /// let is_less = |n1: &i32, n2: &i32| n1.lt(n2);
+    /// tw.push_by(0, 7, is_less);
+    /// tw.push_by(5, 3, is_less);
+    /// assert_eq!(tw.peek(), Some(&7));
+    ///
+    /// tw.advance_to(10);
+    /// assert_eq!(tw.peek(), Some(&3));
+    /// ```
+    pub fn peek(&self) -> Option<&T> {
+        self.mq.peek().map(|(_, item)| item)
+    }
+
+    /// Provides a peek to the timestamp of the front element, or None.
+    pub fn peek_timestamp(&self) -> Option<I> {
+        self.mq.peek().map(|(at, _)| *at)
+    }
+
+    /// Moves the window to end at `now`, evicting the front elements that
+    /// expired.
+    pub fn advance_to(&mut self, now: I) {
+        self.advance_to_with(now, drop);
+    }
+
+    /// Moves the window to end at `now` like `advance_to`, handing the front
+    /// elements that expired to `expired`, oldest first.
+    pub fn advance_to_with<X>(&mut self, now: I, mut expired: X)
+    where
+        X: FnMut(T),
+    {
+        while let Some((at, _)) = self.mq.peek() {
+            match at.expires_at(self.span) {
+                Some(deadline) if deadline <= now => {
+                    if let Some((_, item)) = self.mq.pop() {
+                        expired(item);
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Pushes `item` stamped with `at`, first advancing the window to `at` and
+    /// then evicting the elements dominated by `item`.
+    pub fn push_by<F>(&mut self, at: I, item: T, is_less: F)
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        self.check_order(at);
+        self.advance_to(at);
+        self.mq
+            .push_by((at, item), |(_, n1), (_, n2)| is_less(n1, n2));
+    }
+
+    /// Checks, in debug builds, that `at` does not precede the timestamp of
+    /// the newest element.
+    fn check_order(&self, at: I) {
+        if let Some((newest, _)) = self.mq.peek_back() {
+            debug_assert!(
+                *newest <= at,
+                "timestamps must be pushed in non-decreasing order"
+            );
+        }
+    }
+}
+
+impl<T, I: Timestamp, C: Compare<T>> TimedWindow<T, I, C> {
+    /// Pushes `item` stamped with `at`, first advancing the window to `at` and
+    /// then evicting the elements dominated by `item` under the window's
+    /// comparator.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::TimedWindow;
+    ///
+    /// let mut tw = TimedWindow::<i32, u64>::min(10);
+    /// tw.push(0, 1);
+    /// tw.push(5, 4);
+    /// tw.push(10, 6);
+    ///
+    /// assert_eq!(tw.peek(), Some(&4));
+    /// ```
+    pub fn push(&mut self, at: I, item: T) {
+        self.check_order(at);
+        self.advance_to(at);
+        let cmp = &self.cmp;
+        self.mq
+            .push_by((at, item), |(_, n1), (_, n2)| cmp.is_less(n1, n2));
+    }
+
+    /// Pushes `item` stamped with `at` like `push`, handing the elements that
+    /// expired by `at` to `expired` and then those dominated by `item` to
+    /// `evicted`.
+    pub fn push_with<X, E>(&mut self, at: I, item: T, expired: X, mut evicted: E)
+    where
+        X: FnMut(T),
+        E: FnMut(T),
+    {
+        self.check_order(at);
+        self.advance_to_with(at, expired);
+        let cmp = &self.cmp;
+        self.mq.push_by_with_evicted(
+            (at, item),
+            |(_, n1), (_, n2)| cmp.is_less(n1, n2),
+            |(_, n)| evicted(n),
+        );
+    }
+}
+
+/// Serializes the comparator, the span and the retained elements with their
+/// timestamps.
+#[cfg(feature = "serde")]
+impl<T, I, C> Serialize for TimedWindow<T, I, C>
+where
+    T: Serialize,
+    I: Timestamp + Serialize,
+    I::Span: Serialize,
+    C: Serialize,
+{
+    fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        let mut state = serializer.serialize_struct("TimedWindow", 3)?;
+        state.serialize_field("cmp", &self.cmp)?;
+        state.serialize_field("span", &self.span)?;
+        state.serialize_field("items", &self.mq.serialize_items())?;
+        state.end()
+    }
+}
+
+/// Restores the window as it was serialized, rejecting a snapshot whose
+/// timestamps decrease or whose elements are not monotonic under its
+/// comparator.
+#[cfg(feature = "serde")]
+impl<'de, T, I, C> Deserialize<'de> for TimedWindow<T, I, C>
+where
+    T: Deserialize<'de>,
+    I: Timestamp + Deserialize<'de>,
+    I::Span: Deserialize<'de>,
+    C: Deserialize<'de> + Compare<T>,
+{
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<TimedWindow<T, I, C>, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "TimedWindow")]
+        struct Snapshot<T, S, C> {
+            cmp: C,
+            span: S,
+            items: Vec<T>,
+        }
+
+        let snapshot: Snapshot<(I, T), I::Span, C> = Snapshot::deserialize(deserializer)?;
+        let mq = MonotonicQueue::restore(Direction::Max, Policy::NonStrict, snapshot.items)
+            .ok_or_else(|| D::Error::custom("snapshot overflows the queue storage"))?;
+        if mq
+            .iter()
+            .zip(mq.iter().skip(1))
+            .any(|((at1, _), (at2, _))| at2 < at1)
+        {
+            return Err(D::Error::custom("snapshot timestamps are out of order"));
+        }
+        let cmp = snapshot.cmp;
+        mq.validate_by(|(_, n1), (_, n2)| cmp.is_less(n1, n2))
+            .map_err(D::Error::custom)?;
+        Ok(TimedWindow {
+            mq,
+            cmp,
+            span: snapshot.span,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::{TimedWindow, Timestamp};
+
+    #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+    struct FakeInstant(u32);
+
+    impl Timestamp for FakeInstant {
+        type Span = u32;
+
+        fn expires_at(self, span: u32) -> Option<FakeInstant> {
+            self.0.checked_add(span).map(FakeInstant)
+        }
+    }
+
+    #[test]
+    fn timed_window_with_fake_clock() {
+        let mut tw = TimedWindow::new(30);
+
+        let is_less = |n1: &i32, n2: &i32| n1.lt(n2);
+        tw.push_by(FakeInstant(0), 9, is_less);
+        tw.push_by(FakeInstant(10), 4, is_less);
+        tw.push_by(FakeInstant(20), 6, is_less);
+        assert_eq!(tw.peek(), Some(&9));
+
+        tw.advance_to(FakeInstant(29));
+        assert_eq!(tw.peek(), Some(&9));
+
+        tw.advance_to(FakeInstant(30));
+        assert_eq!(tw.peek(), Some(&6));
+        assert_eq!(tw.peek_timestamp(), Some(FakeInstant(20)));
+
+        tw.advance_to(FakeInstant(50));
+        assert_eq!(tw.peek(), None);
+    }
+
+    #[test]
+    fn timed_window_with_instant() {
+        let mut tw = TimedWindow::new(Duration::from_secs(30));
+        let start = Instant::now();
+
+        let is_less = |n1: &i32, n2: &i32| n1.gt(n2);
+        tw.push_by(start, 1, is_less);
+        tw.push_by(start + Duration::from_secs(10), 5, is_less);
+
+        tw.advance_to(start + Duration::from_secs(30));
+        assert_eq!(tw.peek(), Some(&5));
+    }
+
+    #[test]
+    fn timed_window_push_with() {
+        let mut tw = TimedWindow::<i32, u64>::min(10);
+        tw.push(0, 2);
+        tw.push(3, 7);
+        tw.push(6, 9);
+
+        let (mut expired, mut evicted) = (Vec::new(), Vec::new());
+        tw.push_with(10, 8, |n| expired.push(n), |n| evicted.push(n));
+        tw.advance_to_with(14, |n| expired.push(n));
+
+        assert_eq!(expired, vec![2, 7]);
+        assert_eq!(evicted, vec![9]);
+        assert_eq!(tw.peek(), Some(&8));
+    }
+
+    #[test]
+    #[cfg_attr(
+        debug_assertions,
+        should_panic(expected = "timestamps must be pushed in non-decreasing order")
+    )]
+    fn timed_window_push_checks_order() {
+        let mut tw = TimedWindow::<i32, u64>::max(10);
+        tw.push(20, 7);
+        tw.push(5, 3);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn timed_window_serde() {
+        let mut tw: TimedWindow<i32, u64> = TimedWindow::min(10);
+        tw.push(0, 3);
+        tw.push(4, 5);
+
+        let json = serde_json::to_string(&tw).unwrap();
+        assert_eq!(json, r#"{"cmp":"Min","span":10,"items":[[0,3],[4,5]]}"#);
+        let mut restored: TimedWindow<i32, u64> = serde_json::from_str(&json).unwrap();
+        restored.advance_to(10);
+        assert_eq!(restored.peek(), Some(&5));
+
+        for corrupted in [
+            r#"{"cmp":"Min","span":10,"items":[[0,5],[4,3]]}"#,
+            r#"{"cmp":"Min","span":10,"items":[[4,3],[0,5]]}"#,
+        ] {
+            assert!(serde_json::from_str::<TimedWindow<i32, u64>>(corrupted).is_err());
+        }
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::{storage, Compare, DequeStorage, Direction, MonotonicQueue, Policy};
+
+/// A change made to the storage of an [`UndoQueue`], along with what undoing
+/// it takes.
+enum Change<T> {
+    /// An element was pushed at the back; undone by popping it.
+    PushedBack,
+    /// An element was pushed at the front; undone by popping it.
+    PushedFront,
+    /// An element was removed from the back; undone by pushing it back.
+    RemovedBack(T),
+    /// An element was removed from the front; undone by pushing it back.
+    RemovedFront(T),
+}
+
+/// The changes made to an [`UndoQueue`] since its last commit, each stamped
+/// with a number never reused, so that a checkpoint can tell whether the
+/// changes preceding it were rolled back since.
+struct Log<T> {
+    changes: Vec<(u64, Change<T>)>,
+    stamp: u64,
+}
+
+impl<T> Log<T> {
+    fn record(&mut self, change: Change<T>) {
+        self.stamp += 1;
+        self.changes.push((self.stamp, change));
+    }
+
+    /// Returns the stamp of the last of the first `len` changes, 0 if `len` is
+    /// zero, or None if fewer changes are recorded.
+    fn stamp_at(&self, len: usize) -> Option<u64> {
+        match len.checked_sub(1) {
+            None => Some(0),
+            Some(index) => self.changes.get(index).map(|(stamp, _)| *stamp),
+        }
+    }
+}
+
+/// A point in the history of an [`UndoQueue`] that it can be rolled back to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Checkpoint {
+    epoch: u64,
+    len: usize,
+    stamp: u64,
+}
+
+/// Undo Queue is a [`MonotonicQueue`] that records every change made to it,
+/// including the elements pushes evict, so that it can be rolled back to a
+/// [`Checkpoint`].
+///
+/// Rolling back takes time proportional to the number of changes undone.
+/// Changes are recorded until `commit` discards them, which also invalidates
+/// every checkpoint taken so far.
+///
+/// # Example
+/// ```
+/// use monotonicqueue::{MonotonicQueue, UndoQueue};
+///
+/// let mut uq = UndoQueue::new(MonotonicQueue::max());
+/// uq.push(5);
+/// uq.push(3);
+///
+/// let checkpoint = uq.checkpoint();
+/// uq.push(9);
+/// uq.pop();
+/// assert!(uq.queue().is_empty());
+///
+/// uq.rollback_to(checkpoint);
+/// assert!(uq.queue().iter().eq(&[5, 3]));
+/// ```
+pub struct UndoQueue<T, C = Direction, S = VecDeque<T>> {
+    mq: MonotonicQueue<T, C, S>,
+    log: Log<T>,
+    epoch: u64,
+}
+
+impl<T, C, S: DequeStorage<T>> UndoQueue<T, C, S> {
+    /// Create an undo queue recording the changes made to `mq` from now on.
+    pub fn new(mq: MonotonicQueue<T, C, S>) -> UndoQueue<T, C, S> {
+        UndoQueue {
+            mq,
+            log: Log {
+                changes: Vec::new(),
+                stamp: 0,
+            },
+            epoch: 0,
+        }
+    }
+
+    /// Returns the underlying queue, for inspection.
+    pub fn queue(&self) -> &MonotonicQueue<T, C, S> {
+        &self.mq
+    }
+
+    /// Returns the underlying queue, discarding the recorded changes.
+    pub fn into_inner(self) -> MonotonicQueue<T, C, S> {
+        self.mq
+    }
+
+    /// Returns a checkpoint of the current state.
+    pub fn checkpoint(&self) -> Checkpoint {
+        let len = self.log.changes.len();
+        Checkpoint {
+            epoch: self.epoch,
+            len,
+            stamp: self.log.stamp_at(len).unwrap_or_default(),
+        }
+    }
+
+    /// Restores the exact state the queue was in when `checkpoint` was taken,
+    /// undoing every change made since.
+    ///
+    /// # Panics
+    /// Panics if `checkpoint` was taken before the last `commit`, or after a
+    /// state that was already rolled back.
+    pub fn rollback_to(&mut self, checkpoint: Checkpoint) {
+        assert!(
+            checkpoint.epoch == self.epoch
+                && self.log.stamp_at(checkpoint.len) == Some(checkpoint.stamp),
+            "checkpoint is no longer valid"
+        );
+        let (dq, _, _) = self.mq.parts_mut();
+        // Every element pushed back was removed by a logged change, which left
+        // room for it in the storage.
+        for (_, change) in self.log.changes.drain(checkpoint.len..).rev() {
+            match change {
+                Change::PushedBack => drop(dq.pop_back()),
+                Change::PushedFront => drop(dq.pop_front()),
+                Change::RemovedBack(item) => drop(dq.push_back(item)),
+                Change::RemovedFront(item) => drop(dq.push_front(item)),
+            }
+        }
+    }
+
+    /// Discards the recorded changes, keeping the current state. Every
+    /// checkpoint taken so far is invalidated.
+    pub fn commit(&mut self) {
+        self.log.changes.clear();
+        self.epoch += 1;
+    }
+
+    /// Removes the front element and returns a clone of it, the element being
+    /// kept for rollback, or None if empty.
+    pub fn pop(&mut self) -> Option<T>
+    where
+        T: Clone,
+    {
+        let (dq, _, _) = self.mq.parts_mut();
+        let item = dq.pop_front()?;
+        self.log.record(Change::RemovedFront(item.clone()));
+        Some(item)
+    }
+
+    /// Removes the back element and returns a clone of it, the element being
+    /// kept for rollback, or None if empty.
+    pub fn pop_back(&mut self) -> Option<T>
+    where
+        T: Clone,
+    {
+        let (dq, _, _) = self.mq.parts_mut();
+        let item = dq.pop_back()?;
+        self.log.record(Change::RemovedBack(item.clone()));
+        Some(item)
+    }
+
+    /// Pushes `item` to the back as
+    /// [`MonotonicQueue::push_by`](MonotonicQueue::push_by) does, recording
+    /// the elements it evicts.
+    ///
+    /// # Panics
+    /// Panics if the storage is bounded and full.
+    pub fn push_by<F>(&mut self, item: T, is_less: F)
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let (dq, _, policy) = self.mq.parts_mut();
+        push_back(dq, &mut self.log, policy, item, is_less);
+    }
+
+    /// Pushes `item` to the front as
+    /// [`MonotonicQueue::push_front_by`](MonotonicQueue::push_front_by) does,
+    /// recording the element it replaces.
+    ///
+    /// # Panics
+    /// Panics if the storage is bounded and full.
+    pub fn push_front_by<F>(&mut self, item: T, is_less: F) -> Result<(), T>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let (dq, _, policy) = self.mq.parts_mut();
+        push_front(dq, &mut self.log, policy, item, is_less)
+    }
+}
+
+impl<T, C: Compare<T>, S: DequeStorage<T>> UndoQueue<T, C, S> {
+    /// Pushes `item` to the back under the queue's comparator, recording the
+    /// elements it evicts.
+    ///
+    /// # Panics
+    /// Panics if the storage is bounded and full.
+    pub fn push(&mut self, item: T) {
+        let (dq, cmp, policy) = self.mq.parts_mut();
+        push_back(dq, &mut self.log, policy, item, |n1, n2| {
+            cmp.is_less(n1, n2)
+        });
+    }
+
+    /// Pushes `item` to the front under the queue's comparator, recording the
+    /// element it replaces.
+    ///
+    /// # Panics
+    /// Panics if the storage is bounded and full.
+    pub fn push_front(&mut self, item: T) -> Result<(), T> {
+        let (dq, cmp, policy) = self.mq.parts_mut();
+        push_front(dq, &mut self.log, policy, item, |n1, n2| {
+            cmp.is_less(n1, n2)
+        })
+    }
+}
+
+impl<T, C, S: DequeStorage<T>> From<MonotonicQueue<T, C, S>> for UndoQueue<T, C, S> {
+    fn from(mq: MonotonicQueue<T, C, S>) -> UndoQueue<T, C, S> {
+        UndoQueue::new(mq)
+    }
+}
+
+impl<T, C, S: fmt::Debug> fmt::Debug for UndoQueue<T, C, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UndoQueue")
+            .field("mq", &self.mq)
+            .field("changes", &self.log.changes.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Pushes `item` to the back of `dq`, logging the changes made.
+fn push_back<T, S, F>(dq: &mut S, log: &mut Log<T>, policy: Policy, item: T, is_less: F)
+where
+    S: DequeStorage<T>,
+    F: Fn(&T, &T) -> bool,
+{
+    let pushed = storage::push_back(
+        dq,
+        policy,
+        item,
+        |existing_item, item| is_less(existing_item, item),
+        |existing_item, item| is_less(item, existing_item),
+        |item| log.record(Change::RemovedBack(item)),
+    );
+    match pushed {
+        Ok(true) => log.record(Change::PushedBack),
+        // The refused item was logged last, as if evicted.
+        Ok(false) => drop(log.changes.pop()),
+        Err(_) => panic!("monotonic queue storage is full"),
+    }
+}
+
+/// Pushes `item` to the front of `dq`, logging the changes made.
+fn push_front<T, S, F>(
+    dq: &mut S,
+    log: &mut Log<T>,
+    policy: Policy,
+    item: T,
+    is_less: F,
+) -> Result<(), T>
+where
+    S: DequeStorage<T>,
+    F: Fn(&T, &T) -> bool,
+{
+    storage::push_front(
+        dq,
+        policy,
+        item,
+        |existing_item, item| is_less(existing_item, item),
+        |existing_item, item| is_less(item, existing_item),
+        |item| log.record(Change::RemovedFront(item)),
+    )?;
+    log.record(Change::PushedFront);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{xorshift, POLICIES};
+    use crate::{ByKey, Direction, MonotonicQueue, UndoQueue};
+
+    type Tagged = (u8, usize);
+
+    #[test]
+    fn undo_queue_rollback_restores_state() {
+        let mut seed = 0x1234_5678_u32;
+        let mut next = || xorshift(&mut seed) as usize;
+
+        for direction in [Direction::Max, Direction::Min] {
+            for policy in POLICIES {
+                let key: fn(&Tagged) -> u8 = |(value, _)| *value;
+                let mq =
+                    MonotonicQueue::with_comparator(ByKey::new(direction, key)).with_policy(policy);
+                let mut uq = UndoQueue::new(mq);
+                let mut checkpoints = Vec::new();
+
+                for tag in 0..5000 {
+                    let item = ((next() % 4) as u8, tag);
+                    match next() % 10 {
+                        0..=2 => uq.push(item),
+                        3 => drop(uq.push_front(item)),
+                        4 => drop(uq.pop()),
+                        5 => drop(uq.pop_back()),
+                        6 | 7 => checkpoints.push((uq.checkpoint(), uq.queue().clone())),
+                        8 => {
+                            let depth = next() % (checkpoints.len() + 1);
+                            if let Some((checkpoint, snapshot)) = checkpoints.drain(depth..).next()
+                            {
+                                uq.rollback_to(checkpoint);
+                                assert_eq!(uq.queue(), &snapshot);
+                            }
+                        }
+                        _ if next() % 8 == 0 => {
+                            uq.commit();
+                            checkpoints.clear();
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "checkpoint is no longer valid")]
+    fn undo_queue_stale_checkpoint_panics() {
+        let mut uq = UndoQueue::new(MonotonicQueue::min());
+        uq.push(1);
+        let checkpoint = uq.checkpoint();
+        uq.push(0);
+        uq.commit();
+
+        uq.rollback_to(checkpoint);
+    }
+
+    #[test]
+    #[should_panic(expected = "checkpoint is no longer valid")]
+    fn undo_queue_rolled_back_checkpoint_panics() {
+        let mut uq = UndoQueue::new(MonotonicQueue::max());
+        uq.push(5);
+        let early = uq.checkpoint();
+        uq.push(3);
+        let late = uq.checkpoint();
+        uq.rollback_to(early);
+        uq.push(1);
+
+        uq.rollback_to(late);
+    }
+}
@@ -0,0 +1,392 @@
+#[cfg(feature = "serde")]
+use serde::de::Error as _;
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Compare, Direction, Iter, MonotonicQueue, Policy};
+
+/// Sliding Window is a monotonic queue over the last `window` pushed elements.
+///
+/// Every pushed element is assigned a logical position, starting at `0`. When a
+/// new element is pushed, elements at the front whose position fell out of the
+/// window are evicted before the dominance rule of [`MonotonicQueue::push_by`]
+/// is applied, so `peek` always returns the extremum of the current window.
+pub struct SlidingWindow<T, C = Direction> {
+    mq: MonotonicQueue<(usize, T)>,
+    cmp: C,
+    window: usize,
+    next: usize,
+}
+
+impl<T> SlidingWindow<T> {
+    /// Create an empty sliding window covering the last `window` elements.
+    ///
+    /// # Panics
+    /// Panics if `window` is zero.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::SlidingWindow;
+    ///
+    /// let sw: SlidingWindow<i32> = SlidingWindow::new(3);
+    /// ```
+    pub fn new(window: usize) -> SlidingWindow<T> {
+        SlidingWindow::with_comparator(window, Direction::Max)
+    }
+
+    /// Create an empty sliding window whose `peek` returns the maximum of the
+    /// last `window` elements.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::SlidingWindow;
+    ///
+    /// let mut sw = SlidingWindow::max(2);
+    /// sw.push(3);
+    /// sw.push(1);
+    /// sw.push(2);
+    ///
+    /// assert_eq!(sw.peek(), Some(&2));
+    /// ```
+    pub fn max(window: usize) -> SlidingWindow<T> {
+        SlidingWindow::with_comparator(window, Direction::Max)
+    }
+
+    /// Create an empty sliding window whose `peek` returns the minimum of the
+    /// last `window` elements.
+    pub fn min(window: usize) -> SlidingWindow<T> {
+        SlidingWindow::with_comparator(window, Direction::Min)
+    }
+}
+
+impl<T, C> SlidingWindow<T, C> {
+    /// Create an empty sliding window covering the last `window` elements,
+    /// applying `cmp` on every `push`.
+    ///
+    /// # Panics
+    /// Panics if `window` is zero.
+    pub fn with_comparator(window: usize, cmp: C) -> SlidingWindow<T, C> {
+        assert!(window > 0, "window length must be non-zero");
+        SlidingWindow {
+            mq: MonotonicQueue::new(),
+            cmp,
+            window,
+            next: 0,
+        }
+    }
+
+    /// Returns the window length.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Returns the logical position the next pushed element will receive.
+    pub fn next_position(&self) -> usize {
+        self.next
+    }
+
+    /// Returns the comparator applied on `push`.
+    pub fn comparator(&self) -> &C {
+        &self.cmp
+    }
+
+    /// Provides a peek to the extremum of the current window, or None.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::SlidingWindow;
+    ///
+    /// let mut sw = SlidingWindow::new(2);
+    ///
+    /// let is_less = |n1: &i32, n2: &i32| n1.lt(n2);
+    /// sw.push_by(3, is_less);
+    /// sw.push_by(1, is_less);
+    /// assert_eq!(sw.peek(), Some(&3));
+    ///
+    /// sw.push_by(2, is_less);
+    /// assert_eq!(sw.peek(), Some(&2));
+    /// ```
+    pub fn peek(&self) -> Option<&T> {
+        self.mq.peek().map(|(_, item)| item)
+    }
+
+    /// Pushes `item` at the next logical position, evicting the elements that
+    /// fell out of the window and then those dominated by `item`.
+    pub fn push_by<F>(&mut self, item: T, is_less: F)
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let position = self.slide(drop);
+        self.mq
+            .push_by((position, item), |(_, n1), (_, n2)| is_less(n1, n2));
+    }
+
+    /// Assigns the next logical position and evicts the front elements that
+    /// fall out of the window ending there, handing them to `expired`.
+    fn slide<E>(&mut self, mut expired: E) -> usize
+    where
+        E: FnMut(T),
+    {
+        let position = self.next;
+        self.next += 1;
+
+        while let Some((front, _)) = self.mq.peek() {
+            if is_expired(*front, position, self.window) {
+                if let Some((_, item)) = self.mq.pop() {
+                    expired(item);
+                }
+            } else {
+                break;
+            }
+        }
+        position
+    }
+}
+
+/// Returns true if the element at position `front` is out of the window of
+/// `window` elements ending at `position`, which never precedes `front`.
+pub(crate) fn is_expired(front: usize, position: usize, window: usize) -> bool {
+    position - front >= window
+}
+
+impl<T, C: Compare<T>> SlidingWindow<T, C> {
+    /// Pushes `item` at the next logical position, evicting the elements that
+    /// fell out of the window and then those dominated by `item` under the
+    /// window's comparator.
+    pub fn push(&mut self, item: T) {
+        let position = self.slide(drop);
+        let cmp = &self.cmp;
+        self.mq
+            .push_by((position, item), |(_, n1), (_, n2)| cmp.is_less(n1, n2));
+    }
+
+    /// Pushes `item` like `push`, handing the elements that fell out of the
+    /// window to `expired` and then those dominated by `item` to `evicted`.
+    ///
+    /// # Example
+    /// ```
+    /// use monotonicqueue::SlidingWindow;
+    ///
+    /// let mut sw = SlidingWindow::max(2);
+    /// sw.push(5);
+    /// sw.push(3);
+    ///
+    /// let (mut expired, mut evicted) = (Vec::new(), Vec::new());
+    /// sw.push_with(4, |n| expired.push(n), |n| evicted.push(n));
+    ///
+    /// assert_eq!(expired, vec![5]);
+    /// assert_eq!(evicted, vec![3]);
+    /// ```
+    pub fn push_with<X, E>(&mut self, item: T, expired: X, mut evicted: E)
+    where
+        X: FnMut(T),
+        E: FnMut(T),
+    {
+        let position = self.slide(expired);
+        let cmp = &self.cmp;
+        self.mq.push_by_with_evicted(
+            (position, item),
+            |(_, n1), (_, n2)| cmp.is_less(n1, n2),
+            |(_, n)| evicted(n),
+        );
+    }
+}
+
+impl<T, C: Compare<T>> SlidingWindow<T, C> {
+    /// Create a window holding `items` with their positions front to back as
+    /// they are, or the reason they cannot be the state of such a window.
+    pub(crate) fn restore(
+        cmp: C,
+        window: usize,
+        next: usize,
+        items: Vec<(usize, T)>,
+    ) -> Result<SlidingWindow<T, C>, &'static str> {
+        let mq = MonotonicQueue::restore(Direction::Max, Policy::NonStrict, items)
+            .ok_or("snapshot overflows the queue storage")?;
+        check_positions(&mq, window, next)?;
+        if mq
+            .validate_by(|(_, n1), (_, n2)| cmp.is_less(n1, n2))
+            .is_err()
+        {
+            return Err("snapshot elements are out of monotonic order");
+        }
+        Ok(SlidingWindow {
+            mq,
+            cmp,
+            window,
+            next,
+        })
+    }
+
+    /// Returns the retained elements with their positions, front to back.
+    pub(crate) fn positioned(&self) -> Iter<'_, (usize, T)> {
+        self.mq.iter()
+    }
+}
+
+/// Checks that the positions of `mq`, front to back, are strictly increasing,
+/// precede `next` and have not expired from a window of `window` elements, and
+/// that the newest element, which is always retained, is at the back.
+pub(crate) fn check_positions<T>(
+    mq: &MonotonicQueue<(usize, T)>,
+    window: usize,
+    next: usize,
+) -> Result<(), &'static str> {
+    if window == 0 {
+        return Err("window length must be non-zero");
+    }
+    match (mq.peek_back(), next.checked_sub(1)) {
+        (None, None) => {}
+        (Some((back, _)), Some(newest)) if *back == newest => {}
+        _ => return Err("snapshot lost the newest element"),
+    }
+    let mut expected = 0;
+    for (position, _) in mq {
+        if *position < expected || *position >= next {
+            return Err("snapshot positions are out of order");
+        }
+        expected = position + 1;
+    }
+    // The front does not follow the newest element, at `next - 1`, and is
+    // checked as `push` would expire it.
+    if let Some((front, _)) = mq.peek() {
+        if is_expired(*front, next - 1, window) {
+            return Err("snapshot holds expired elements");
+        }
+    }
+    Ok(())
+}
+
+/// Serializes the comparator, the window length, the next position and the
+/// retained elements with their positions.
+#[cfg(feature = "serde")]
+impl<T: Serialize, C: Serialize> Serialize for SlidingWindow<T, C> {
+    fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        let mut state = serializer.serialize_struct("SlidingWindow", 4)?;
+        state.serialize_field("cmp", &self.cmp)?;
+        state.serialize_field("window", &self.window)?;
+        state.serialize_field("next", &self.next)?;
+        state.serialize_field("items", &self.mq.serialize_items())?;
+        state.end()
+    }
+}
+
+/// Restores the window as it was serialized, rejecting a snapshot whose
+/// positions are inconsistent or whose elements are not monotonic under its
+/// comparator.
+#[cfg(feature = "serde")]
+impl<'de, T, C> Deserialize<'de> for SlidingWindow<T, C>
+where
+    T: Deserialize<'de>,
+    C: Deserialize<'de> + Compare<T>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<SlidingWindow<T, C>, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "SlidingWindow")]
+        struct Snapshot<T, C> {
+            cmp: C,
+            window: usize,
+            next: usize,
+            items: Vec<(usize, T)>,
+        }
+
+        let snapshot: Snapshot<T, C> = Snapshot::deserialize(deserializer)?;
+        SlidingWindow::restore(snapshot.cmp, snapshot.window, snapshot.next, snapshot.items)
+            .map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{SlidingExt, SlidingWindow, WarmUp};
+
+    #[test]
+    fn sliding_window_max() {
+        let mut sw = SlidingWindow::new(3);
+
+        let is_less = |n1: &i32, n2: &i32| n1.lt(n2);
+        let mut maxima = Vec::new();
+        for n in [1, 3, -1, -3, 5, 3, 6, 7] {
+            sw.push_by(n, is_less);
+            maxima.push(*sw.peek().unwrap());
+        }
+
+        assert_eq!(maxima, vec![1, 3, 3, 3, 5, 5, 6, 7]);
+    }
+
+    #[test]
+    fn sliding_window_expires_front() {
+        let mut sw = SlidingWindow::new(2);
+
+        let is_less = |n1: &i32, n2: &i32| n1.gt(n2);
+        sw.push_by(1, is_less);
+        sw.push_by(5, is_less);
+        sw.push_by(4, is_less);
+
+        assert_eq!(sw.peek(), Some(&4));
+        assert_eq!(sw.next_position(), 3);
+    }
+
+    #[test]
+    fn sliding_window_unbounded() {
+        let mut sw = SlidingWindow::max(usize::MAX);
+        for n in [5, 9, 1] {
+            sw.push(n);
+        }
+        assert_eq!(sw.peek(), Some(&9));
+
+        let maxima: Vec<_> = [5, 9, 1]
+            .into_iter()
+            .sliding_max(usize::MAX)
+            .warm_up(WarmUp::Partial)
+            .collect();
+        assert_eq!(maxima, vec![5, 9, 9]);
+    }
+
+    #[test]
+    fn sliding_window_min() {
+        let mut sw = SlidingWindow::min(3);
+
+        let mut minima = Vec::new();
+        for n in [4, 2, 12, 11, -5, 7] {
+            sw.push(n);
+            minima.push(*sw.peek().unwrap());
+        }
+
+        assert_eq!(minima, vec![4, 2, 2, 2, -5, -5]);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn sliding_window_serde() {
+        let mut sw = SlidingWindow::max(3);
+        for n in [5, 1, 4, 2, 3] {
+            sw.push(n);
+        }
+
+        let json = serde_json::to_string(&sw).unwrap();
+        assert_eq!(
+            json,
+            r#"{"cmp":"Max","window":3,"next":5,"items":[[2,4],[4,3]]}"#
+        );
+        let mut restored: SlidingWindow<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.peek(), Some(&4));
+        restored.push(0);
+        assert_eq!(restored.peek(), Some(&3));
+
+        for corrupted in [
+            r#"{"cmp":"Max","window":3,"next":5,"items":[[2,3],[4,4]]}"#,
+            r#"{"cmp":"Max","window":3,"next":5,"items":[[4,4],[2,3]]}"#,
+            r#"{"cmp":"Max","window":3,"next":5,"items":[[1,4],[4,3]]}"#,
+            r#"{"cmp":"Max","window":3,"next":5,"items":[[2,4],[5,3]]}"#,
+            r#"{"cmp":"Max","window":0,"next":5,"items":[]}"#,
+            r#"{"cmp":"Max","window":3,"next":5,"items":[]}"#,
+            r#"{"cmp":"Max","window":3,"next":5,"items":[[2,4]]}"#,
+            r#"{"cmp":"Max","window":3,"next":0,"items":[[0,4]]}"#,
+        ] {
+            assert!(serde_json::from_str::<SlidingWindow<i32>>(corrupted).is_err());
+        }
+    }
+}